@@ -0,0 +1,123 @@
+/*! The benchmarks of walking the dirs, searching the raw text and searching the symbols.
+
+    The files are generated in a temp dir, set `SEARCH_CODE_BENCH_FILES` to change the count
+    of the files, 200 by default.
+
+    *Use:* `cargo bench`, or `cargo bench -- scan_dir` to run one of them.
+*/
+
+use std::{fs, hint::black_box, path::Path};
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use search_code::{
+    parser_lang::SpecifyParser,
+    search::{find_key_file, get_symbols, SearchCode, SearchOptions},
+    Options::{IgnoreDir, Lang},
+};
+use tempfile::TempDir;
+
+/// The functions in each generated file.
+const FUNCTIONS_PER_FILE: usize = 50;
+
+/// The count of the generated files, from `SEARCH_CODE_BENCH_FILES`.
+fn files_count() -> usize {
+    std::env::var("SEARCH_CODE_BENCH_FILES")
+        .ok()
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(200)
+}
+
+/// Generate the rust files in the nested dirs, 10 files in each dir.
+fn generate_tree(files: usize) -> TempDir {
+    let dir = tempfile::tempdir().expect("Can't create the temp dir");
+    for index in 0..files {
+        let sub_dir = dir.path().join(format!("module_{}", index / 10));
+        fs::create_dir_all(&sub_dir).expect("Can't create the dir");
+        fs::write(sub_dir.join(format!("file_{index}.rs")), rust_code(index))
+            .expect("Can't write the file");
+    }
+    dir
+}
+
+/// The rust code with `FUNCTIONS_PER_FILE` functions and a struct.
+fn rust_code(index: usize) -> String {
+    let mut code = format!("pub struct Item{index} {{\n    value: usize,\n}}\n\n");
+    for function in 0..FUNCTIONS_PER_FILE {
+        code.push_str(&format!(
+            "pub fn handle_{index}_{function}(value: usize) -> usize {{\n    \
+             // the target of the raw text search.\n    \
+             let target = value * {function};\n    \
+             target + {index}\n}}\n\n"
+        ));
+    }
+    code
+}
+
+fn bench_scan_dir(c: &mut Criterion) {
+    let dir = generate_tree(files_count());
+    c.bench_function("scan_dir", |b| {
+        b.iter(|| {
+            let mut search = SearchCode::build(&Lang::Rust, IgnoreDir::new());
+            black_box(search.scan_dir(dir.path()).unwrap())
+        })
+    });
+}
+
+fn bench_find_key_file(c: &mut Criterion) {
+    let dir = generate_tree(1);
+    let file = dir.path().join("module_0/file_0.rs");
+    let file = file.to_str().unwrap();
+    let options = SearchOptions::default();
+    c.bench_function("find_key_file", |b| {
+        b.iter(|| {
+            black_box(find_key_file(file, "target", &mut SpecifyParser::new(), &options).unwrap())
+        })
+    });
+}
+
+fn bench_get_symbols(c: &mut Criterion) {
+    let dir = generate_tree(1);
+    let file = dir.path().join("module_0/file_0.rs");
+    let file = file.to_str().unwrap();
+    let options = SearchOptions {
+        is_symbol: true,
+        ..Default::default()
+    };
+    // a new parser for each run, so the file is parsed instead of read from the cache.
+    c.bench_function("get_symbols", |b| {
+        b.iter_batched(
+            || SpecifyParser::from_lang(&Lang::Rust),
+            |mut parser| black_box(get_symbols(file, "handle", &mut parser, &options).unwrap()),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+fn bench_search_all(c: &mut Criterion) {
+    let dir = generate_tree(files_count());
+    let options = SearchOptions {
+        is_symbol: true,
+        quiet: true,
+        ..Default::default()
+    };
+    c.bench_function("search_all", |b| {
+        b.iter(|| {
+            let mut search = SearchCode::build(&Lang::Rust, IgnoreDir::new());
+            search.set_options(options.clone());
+            black_box(
+                search
+                    .search_all(&[Path::new(dir.path())], "handle")
+                    .unwrap(),
+            )
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    bench_scan_dir,
+    bench_find_key_file,
+    bench_get_symbols,
+    bench_search_all
+);
+criterion_main!(benches);
@@ -3,11 +3,22 @@
 */
 use std::str::FromStr;
 
+use clap::parser::ValueSource;
 use clap::ArgAction;
 use clap::{Arg, ArgMatches, Command};
+use encoding_rs::Encoding;
 
+use crate::Options::config::Config;
 use crate::Options::constants::*;
-use crate::Options::Lang;
+use crate::Options::{
+    parse_langs, ColorChoice, Lang, LineRange, ModifiedTime, OutputFormat, PathStyle, SortBy,
+    SymbolMatch,
+};
+
+/// Find the encoding by its name, like `gbk`, `shift_jis` or `utf-16le`.
+fn parse_encoding(name: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(name.as_bytes()).ok_or_else(|| format!("unknown encoding: {name}"))
+}
 
 /** Get the command line arguments.
 
@@ -15,7 +26,7 @@ use crate::Options::Lang;
 
 *RETURNS:* The matches from the command line.
 
-```rust
+```no_run
 use search_code::Options::cmd_args::get_args_matches;
 
 let matches = get_args_matches();
@@ -29,17 +40,19 @@ pub fn get_args_matches() -> ArgMatches {
         .author(AUTHOR)
         .args([
             Arg::new("path or file")
-                .help("The path to the file to search in")
+                .help("The paths of the files or the dirs to search in, `-` to read the paths from stdin")
                 .short('p')
+                .num_args(1..)
                 .default_value("."),
             Arg::new("key_to_search")
                 .help("The key to search for in the file")
                 .short('k')
-                .required(true),
+                .required_unless_present_any(["list_symbols", "tags", "index"]),
             Arg::new("language")
-                .help("The language of the file")
+                .help("The languages of the files, comma-separated like rust,python")
                 .short('l')
                 .long("lang")
+                .value_parser(parse_langs)
                 .default_value("any"),
             Arg::new("symbol")
                 .help("The symbol to search for in the file")
@@ -52,6 +65,371 @@ pub fn get_args_matches() -> ArgMatches {
                 .long("ignore")
                 .short('i')
                 .action(ArgAction::Set),
+            Arg::new("output")
+                .help("Write the result to this file instead of the terminal")
+                .short('o')
+                .long("output"),
+            Arg::new("staged")
+                .help("Only search the lines added in the git staging area")
+                .long("staged")
+                .conflicts_with_all(["context_function_body", "write"])
+                .action(ArgAction::SetTrue),
+            Arg::new("symbol_context")
+                .help("Show N lines before and after each symbol definition")
+                .long("symbol-context")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("0"),
+            Arg::new("files_with_matches")
+                .help("Only print the names of the files that contain the key")
+                .short('f')
+                .long("files-with-matches")
+                .action(ArgAction::SetTrue),
+            Arg::new("max_count")
+                .help("Stop searching a file after N lines found")
+                .short('m')
+                .long("max-count")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize)),
+            Arg::new("max_total")
+                .help("Stop the whole search after N lines found")
+                .long("max-total")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize)),
+            Arg::new("exclude_symbol")
+                .help("The symbol names to drop from the result, can be repeated")
+                .long("exclude-symbol")
+                .value_name("NAME")
+                .action(ArgAction::Append),
+            Arg::new("auto")
+                .help("Detect the language of each file from its extension")
+                .long("auto")
+                .action(ArgAction::SetTrue),
+            Arg::new("stdin")
+                .help("Read the newline-separated paths to search from stdin")
+                .long("stdin")
+                .action(ArgAction::SetTrue),
+            Arg::new("include")
+                .help("Only search the files whose name matches the glob, can be repeated")
+                .long("include")
+                .value_name("GLOB")
+                .action(ArgAction::Append),
+            Arg::new("exclude")
+                .help("Don't search the files whose name matches the glob, can be repeated")
+                .long("exclude")
+                .value_name("GLOB")
+                .action(ArgAction::Append),
+            Arg::new("no_summary")
+                .help("Don't print the summary line after the result")
+                .long("no-summary")
+                .action(ArgAction::SetTrue),
+            Arg::new("type")
+                .help(
+                    "Only search the files of the named type, like web or headers, can be repeated",
+                )
+                .long("type")
+                .short('t')
+                .value_name("TYPE")
+                .action(ArgAction::Append),
+            Arg::new("include_git")
+                .help("Also search inside the git dir and files")
+                .long("include-git")
+                .action(ArgAction::SetTrue),
+            Arg::new("symbol_match")
+                .help("How the key matches the symbol names")
+                .long("symbol-match")
+                .value_parser(["regex", "exact", "prefix", "suffix", "fuzzy"])
+                .default_value("regex"),
+            Arg::new("format")
+                .help("The format of the result")
+                .long("format")
+                .value_parser(["text", "json", "jsonl", "sarif", "html", "vimgrep"])
+                .default_value("text"),
+            Arg::new("line_range")
+                .help("Only search the lines in the 1-based inclusive range, like 100:200")
+                .long("line-range")
+                .value_name("START:END")
+                .value_parser(clap::value_parser!(LineRange)),
+            Arg::new("list_files")
+                .help("Print the files that would be searched, then exit without searching")
+                .long("list-files")
+                .action(ArgAction::SetTrue),
+            Arg::new("parse_timeout")
+                .help("Give up parsing a file after MS milliseconds in the symbol search")
+                .long("parse-timeout")
+                .value_name("MS")
+                .value_parser(clap::value_parser!(u64)),
+            Arg::new("replace")
+                .help("Preview the found lines with the key replaced by TEXT, the files are not written")
+                .long("replace")
+                .value_name("TEXT"),
+            Arg::new("regex")
+                .help("Treat the key as a regex in the raw text search")
+                .long("regex")
+                .action(ArgAction::SetTrue),
+            Arg::new("word")
+                .help("Only match the key as a whole word in the raw text search")
+                .long("word")
+                .action(ArgAction::SetTrue),
+            Arg::new("write")
+                .help("Write the replacement of --replace into the files")
+                .long("write")
+                .requires("replace")
+                .action(ArgAction::SetTrue),
+            Arg::new("stats")
+                .help("Print the files searched and the lines found per language")
+                .long("stats")
+                .action(ArgAction::SetTrue),
+            Arg::new("follow_symlinks")
+                .help("Follow the symlinks when scanning the dir, skipped by default")
+                .long("follow-symlinks")
+                .action(ArgAction::SetTrue),
+            Arg::new("quiet")
+                .help("Print nothing, exit with 0 if any line is found, or 1")
+                .short('q')
+                .long("quiet")
+                .action(ArgAction::SetTrue),
+            Arg::new("fuzzy")
+                .help("Rank the symbol names fuzzy matching the key, the same as --symbol-match fuzzy")
+                .long("fuzzy")
+                .conflicts_with("symbol_match")
+                .action(ArgAction::SetTrue),
+            Arg::new("max_filesize")
+                .help("Skip the files larger than BYTES when walking the dir, 0 for no limit")
+                .long("max-filesize")
+                .value_name("BYTES")
+                .value_parser(clap::value_parser!(u64))
+                .default_value("10485760"),
+            Arg::new("line_number_base")
+                .help("The first line number of the output, 0 or 1")
+                .long("line-number-base")
+                .value_parser(["0", "1"])
+                .default_value("1"),
+            Arg::new("no_heading")
+                .help("Print `filename:line:text` for each line, instead of the filename heading")
+                .long("no-heading")
+                .action(ArgAction::SetTrue),
+            Arg::new("preceded_by")
+                .help("Only find the lines whose previous line matches the regex PATTERN, even without --regex")
+                .long("preceded-by")
+                .value_name("PATTERN"),
+            Arg::new("followed_by")
+                .help("Only find the lines whose next line matches the regex PATTERN, even without --regex")
+                .long("followed-by")
+                .value_name("PATTERN"),
+            Arg::new("interactive")
+                .help("Browse the result in an interactive terminal UI")
+                .long("interactive")
+                .action(ArgAction::SetTrue),
+            Arg::new("search_compressed")
+                .help("Decompress the .gz files in the raw text search")
+                .long("search-compressed")
+                .action(ArgAction::SetTrue),
+            Arg::new("sort")
+                .help("The order of the files in the result")
+                .long("sort")
+                .value_parser(["path", "mtime", "matches"])
+                .default_value("path"),
+            Arg::new("query_file")
+                .help("The tree-sitter query file to use instead of the built-in queries, `:?` is replaced with the key")
+                .long("query-file")
+                .value_name("FILE"),
+            Arg::new("context_symbol")
+                .help("Show the function or the class enclosing each match of the raw text search")
+                .long("context-symbol")
+                .action(ArgAction::SetTrue),
+            Arg::new("unique")
+                .help("Print the identical lines of each file once, with the count like (x5)")
+                .long("unique")
+                .action(ArgAction::SetTrue),
+            Arg::new("invert_symbol")
+                .help("Find the symbols whose names do NOT match the key in the symbol search")
+                .long("invert-symbol")
+                .conflicts_with("fuzzy")
+                .action(ArgAction::SetTrue),
+            Arg::new("only_matching")
+                .help("Print only the matched text of each line, one per line")
+                .long("only-matching")
+                .conflicts_with("replace")
+                .action(ArgAction::SetTrue),
+            Arg::new("rev")
+                .help("Search the files at the git revision, like HEAD~3, instead of the working tree")
+                .long("rev")
+                .value_name("REV")
+                .conflicts_with_all(["staged", "stdin", "write", "interactive", "symbol_context", "context_function_body"]),
+            Arg::new("color")
+                .help("When to color the output")
+                .long("color")
+                .value_name("WHEN")
+                .value_parser(["always", "auto", "never"])
+                .default_value("auto"),
+            Arg::new("max_columns")
+                .help("Cut each printed line to N chars, with a note of the chars cut off")
+                .long("max-columns")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize)),
+            Arg::new("not_lang")
+                .help("Skip the files of these languages, comma-separated like python,bash")
+                .long("not-lang")
+                .value_name("LANGS")
+                .value_parser(parse_langs),
+            Arg::new("threads")
+                .help("The threads to search the files, 0 for the number of CPUs, 1 to search one by one")
+                .short('j')
+                .long("threads")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("0"),
+            Arg::new("watch")
+                .help("Search again when the files change, until interrupted")
+                .long("watch")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["stdin", "staged", "diff", "rev", "interactive", "write", "quiet"]),
+            Arg::new("line_number_width")
+                .help("Right-align the line numbers to N columns instead of the widest in each file, 0 for no padding")
+                .long("line-number-width")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize)),
+            Arg::new("ignore_case")
+                .help("Match the key regardless of the case in the raw text search")
+                .long("ignore-case")
+                .action(ArgAction::SetTrue),
+            Arg::new("allow_empty")
+                .help("Search the empty or the whitespace-only key, which matches every line")
+                .long("allow-empty")
+                .action(ArgAction::SetTrue),
+            Arg::new("list_symbols")
+                .help("List all the symbols of the files in the source order, like an outline, the key is not needed")
+                .long("list-symbols")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["invert_symbol", "replace", "rev", "staged", "diff"]),
+            Arg::new("tags")
+                .help("Write all the symbols to a ctags file, `tags` or the --output file")
+                .long("tags")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["invert_symbol", "replace", "rev", "staged", "diff", "interactive", "watch", "quiet"]),
+            Arg::new("modified_after")
+                .help("Only search the files modified after the date like 2024-05-01, or the time ago like 7d")
+                .long("modified-after")
+                .value_name("TIME")
+                .value_parser(clap::value_parser!(ModifiedTime)),
+            Arg::new("modified_before")
+                .help("Only search the files modified before the date like 2024-05-01, or the time ago like 7d")
+                .long("modified-before")
+                .value_name("TIME")
+                .value_parser(clap::value_parser!(ModifiedTime)),
+            Arg::new("mmap")
+                .help("Map the large files into the memory in the raw text search, faster for the big logs")
+                .long("mmap")
+                .action(ArgAction::SetTrue),
+            Arg::new("pcre")
+                .help("Match the --regex key with the fancy-regex engine, for the lookaround and the backreferences. It backtracks, so it can be much slower")
+                .long("pcre")
+                .requires("regex")
+                .action(ArgAction::SetTrue),
+            Arg::new("encoding")
+                .help("Decode the files from the encoding like gbk or shift_jis, instead of UTF-8")
+                .long("encoding")
+                .value_name("NAME")
+                .value_parser(parse_encoding),
+            Arg::new("detect_modeline")
+                .help("Detect the language of the file of unknown extension from its shebang or its modeline, like `-*- mode: rust -*-`")
+                .long("detect-modeline")
+                .action(ArgAction::SetTrue),
+            Arg::new("result_cap")
+                .help("Stop after N lines found and ask to refine the query, 0 for no cap")
+                .long("result-cap")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("10000"),
+            Arg::new("context_function_body")
+                .help("Print the whole function enclosing each match")
+                .long("context-function-body")
+                .action(ArgAction::SetTrue),
+            Arg::new("search_archives")
+                .help("Search the files in the .zip, .tar and .tar.gz archives without extracting them")
+                .long("search-archives")
+                .conflicts_with("context_function_body")
+                .action(ArgAction::SetTrue),
+            Arg::new("signatures")
+                .help("Print the signature of each function symbol, like `fn parse(input: &str) -> Tree`, instead of its line")
+                .long("signatures")
+                .action(ArgAction::SetTrue),
+            Arg::new("index")
+                .help("Write all the symbols to the index file, to search them later with --use-index")
+                .long("index")
+                .value_name("FILE")
+                .conflicts_with_all(["invert_symbol", "replace", "rev", "staged", "diff", "interactive", "watch", "quiet", "tags"]),
+            Arg::new("use_index")
+                .help("Search the symbols in the index file of --index, without parsing the files again")
+                .long("use-index")
+                .value_name("FILE")
+                .conflicts_with_all(["index", "replace", "rev", "staged", "diff", "stdin", "watch", "tags"]),
+            Arg::new("vimgrep")
+                .help("Print `file:line:col:text` for each match, the same as --format vimgrep")
+                .long("vimgrep")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("format"),
+            Arg::new("null")
+                .help("Separate the filenames with NUL instead of newline, for `xargs -0`")
+                .short('0')
+                .long("null")
+                .action(ArgAction::SetTrue),
+            Arg::new("all_files")
+                .help("Search all the files with `-l any`, not only the text files, like the binaries and the lockfiles")
+                .long("all-files")
+                .action(ArgAction::SetTrue),
+            Arg::new("stats_json")
+                .help("Print only the files scanned and matched, the lines found per language and the elapsed time as JSON, the result is written only with -o")
+                .long("stats-json")
+                .conflicts_with("watch")
+                .action(ArgAction::SetTrue),
+            Arg::new("diff")
+                .help("Only search the lines added or modified since the git ref, like main, in the working tree")
+                .long("diff")
+                .value_name("REF")
+                .conflicts_with_all(["staged", "rev", "stdin", "write", "interactive", "symbol_context", "context_function_body"]),
+            Arg::new("no_ignore")
+                .help("Disable all the ignore rules, the --ignore dirs, the config ones and the git dir, to scan the full tree")
+                .long("no-ignore")
+                .action(ArgAction::SetTrue),
+            Arg::new("kind")
+                .help("Only find the symbols of these kinds, comma-separated like function,struct")
+                .long("kind")
+                .value_name("KINDS")
+                .value_delimiter(',')
+                .action(ArgAction::Append),
+            Arg::new("group_by_symbol")
+                .help("Group the matches of the raw text search under the enclosing function or class")
+                .long("group-by-symbol")
+                .conflicts_with_all(["replace", "only_matching", "context_function_body"])
+                .action(ArgAction::SetTrue),
+            Arg::new("after_context_symbol")
+                .help("List the names of the next N symbols after each match")
+                .long("after-context-symbol")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("0"),
+            Arg::new("warn_parse_errors")
+                .help("Warn the files with syntax errors in the symbol search, their symbols may be incomplete")
+                .long("warn-parse-errors")
+                .action(ArgAction::SetTrue),
+            Arg::new("path_style")
+                .help("Show the filenames relative to the current directory or absolute, instead of as scanned")
+                .long("path-style")
+                .value_name("STYLE")
+                .value_parser(["relative", "absolute"]),
+            Arg::new("count")
+                .help("Only print the count of the matched lines in each file")
+                .short('c')
+                .long("count")
+                .conflicts_with_all(["files_with_matches", "replace", "interactive"])
+                .action(ArgAction::SetTrue),
+            Arg::new("count_matches")
+                .help("Only print the count of the key occurrences in each file")
+                .long("count-matches")
+                .conflicts_with_all(["files_with_matches", "replace", "interactive"])
+                .action(ArgAction::SetTrue),
         ])
         .styles(CLI_STYLES.to_owned())
         .get_matches()
@@ -59,19 +437,245 @@ pub fn get_args_matches() -> ArgMatches {
 
 /** command line arguments to config.
 
-*PARAM:* {path} The path to the file to search in.
+*PARAM:* {path} The paths of the files or the dirs to search in.
 
 *PARAM:* {key_to_search} The key to search for in the file.
 
-*PARAM:* {lang} The language of the file.
+*PARAM:* {langs} The languages of the files, `[Lang::Any]` for all the files.
+
+*PARAM:* {output} The file to write the uncolored result to.
+
+*PARAM:* {staged} Only search the lines added in the git staging area.
+
+*PARAM:* {symbol_context} The lines to show around each symbol definition.
+
+*PARAM:* {files_with_matches} Only print the names of the matched files.
+
+*PARAM:* {max_count} The max lines to find in each file.
+
+*PARAM:* {max_total} The max lines to find in all files.
+
+*PARAM:* {exclude_symbol} The symbol names to drop from the result.
+
+*PARAM:* {auto} Detect the language of each file instead of using `lang`.
+
+*PARAM:* {stdin} Read the paths to search from stdin, also set by `-p -`.
+
+*PARAM:* {include} Only search the files matching these globs, if any.
+
+*PARAM:* {exclude} Never search the files matching these globs.
+
+*PARAM:* {no_summary} Don't print the summary line after the result.
+
+*PARAM:* {file_types} Only search the files of these named types, if any.
+
+*PARAM:* {color} When to color the output, `always`, `auto` (if the stdout is a terminal) or `never`.
+
+*PARAM:* {include_git} Also search inside the git dir and files.
+
+*PARAM:* {symbol_match} How the key matches the symbol names, also set by `--fuzzy`.
+
+*PARAM:* {format} The format of the result, `--vimgrep` sets `vimgrep`.
+
+*PARAM:* {line_range} Only search the lines in the range.
+
+*PARAM:* {list_files} Only print the files that would be searched.
+
+*PARAM:* {parse_timeout} The milliseconds to give up parsing a file, the file is skipped with a warning
+when searching a dir.
+
+*PARAM:* {replace} Preview the found lines with the key replaced by it.
+
+*PARAM:* {regex} Treat the key as a regex in the raw text search.
+
+*PARAM:* {word} Only match the key as a whole word in the raw text search.
+
+*PARAM:* {write} Write the replacement into the files, requires `replace`.
+
+*PARAM:* {stats} Print the files and the lines found per language.
+
+*PARAM:* {follow_symlinks} Follow the symlinks when scanning the dir.
+
+*PARAM:* {quiet} Print nothing, just exit with 0 if any line is found, or 1.
+
+*PARAM:* {max_filesize} Skip the files larger than it when walking the dir, `None` for no limit.
+
+*PARAM:* {line_number_base} The first line number of the output, 0 or 1.
+
+*PARAM:* {no_heading} Prefix each line with the filename, instead of the heading.
+
+*PARAM:* {preceded_by} Only find the lines whose previous line matches the regex,
+not escaped or changed by `regex`, `word` and `ignore_case` like the key.
+
+*PARAM:* {followed_by} Only find the lines whose next line matches the regex,
+not escaped or changed by `regex`, `word` and `ignore_case` like the key.
+
+*PARAM:* {interactive} Browse the result in an interactive terminal UI.
+
+*PARAM:* {search_compressed} Decompress the `.gz` files in the raw text search.
+
+*PARAM:* {sort} The order of the files in the result.
+
+*PARAM:* {query_file} The tree-sitter query file to use instead of the built-in queries.
+
+*PARAM:* {context_symbol} Show the function or the class enclosing each raw match.
+
+*PARAM:* {unique} Print the identical lines of each file once, with the count.
+
+*PARAM:* {invert_symbol} Find the symbols not matching the key in the symbol search.
+
+*PARAM:* {only_matching} Print only the matches of the key, or the symbols, one per line.
+
+*PARAM:* {rev} Search the files at the git revision instead of the working tree.
+
+*PARAM:* {max_columns} Cut each printed line to the chars, if set.
+
+*PARAM:* {not_langs} Skip the files of these languages.
+
+*PARAM:* {threads} The threads to search the files, 0 for the number of CPUs. 1 forces the sequential search.
+
+*PARAM:* {watch} Search again when the files change.
+
+*PARAM:* {line_number_width} The width to right-align the line numbers to, if set.
+
+*PARAM:* {ignore_case} Match the key regardless of the case.
+
+*PARAM:* {allow_empty} Search the empty key instead of rejecting it.
+
+*PARAM:* {list_symbols} List all the symbols, the key is ignored.
+
+*PARAM:* {tags} Write all the symbols to a ctags file.
+
+*PARAM:* {modified_after} Only search the files modified after the time.
+
+*PARAM:* {modified_before} Only search the files modified before the time.
+
+*PARAM:* {mmap} Map the large files into the memory in the raw text search.
+
+*PARAM:* {pcre} Match the key with the `fancy-regex` engine.
+
+*PARAM:* {encoding} Decode the files from the encoding, UTF-8 if not set.
+
+*PARAM:* {detect_modeline} Detect the language of the file of unknown extension from its shebang or modeline.
+
+*PARAM:* {result_cap} Stop after the lines found and ask to refine the query, 0 for no cap.
+
+*PARAM:* {context_function_body} Print the whole function enclosing each match.
+
+*PARAM:* {search_archives} Search the files in the archives, named like `dist.zip:src/main.rs`.
+
+*PARAM:* {signatures} Print the signature of each function symbol instead of its line.
+
+*PARAM:* {index} Write all the symbols to the index file.
+
+*PARAM:* {use_index} Search the symbols in the index file.
+
+*PARAM:* {null} End each filename with NUL instead of newline in the files-with-matches mode.
+
+*PARAM:* {all_files} Search all the files in `Lang::Any`, instead of only the text files.
+
+*PARAM:* {stats_json} Print only the statistics as a JSON object, the result is written only to the output file.
+
+*PARAM:* {diff} Only search the lines changed in the working tree since the git ref.
+
+*PARAM:* {no_ignore} Disable all the ignore rules, see `IgnoreDir::no_ignore`.
+
+*PARAM:* {kinds} Only find the symbols of these kinds, see `Options::get_query_kinds`.
+
+*PARAM:* {group_by_symbol} Group the raw matches of each file under their enclosing symbols.
+
+*PARAM:* {after_context_symbol} The next symbols to list after each match, 0 to not.
+
+*PARAM:* {warn_parse_errors} Warn the files that fail to parse cleanly in the symbol search.
+
+*PARAM:* {path_style} Show the filenames relative or absolute, as scanned if `None`.
+
+*PARAM:* {count} Only print the matched lines count of each file.
+
+*PARAM:* {count_matches} Only print the key occurrences count of each file,
+counting every match on the same line. With `count` both are printed as `file:lines:matches`.
 */
 #[derive(Debug, Default)]
 pub struct CommandArgs<'a> {
-    pub path: &'a str,
+    pub path: Vec<&'a str>,
     pub key_to_search: &'a str,
-    pub lang: Lang,
+    pub langs: Vec<Lang>,
     pub is_symbol: bool,
     pub ignore: Vec<&'a str>,
+    pub output: Option<String>,
+    pub staged: bool,
+    pub symbol_context: usize,
+    pub files_with_matches: bool,
+    pub max_count: Option<usize>,
+    pub max_total: Option<usize>,
+    pub exclude_symbol: Vec<&'a str>,
+    pub auto: bool,
+    pub stdin: bool,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub no_summary: bool,
+    pub file_types: Vec<&'a str>,
+    pub color: ColorChoice,
+    pub include_git: bool,
+    pub symbol_match: SymbolMatch,
+    pub format: OutputFormat,
+    pub line_range: Option<LineRange>,
+    pub list_files: bool,
+    pub parse_timeout: Option<u64>,
+    pub replace: Option<&'a str>,
+    pub regex: bool,
+    pub word: bool,
+    pub write: bool,
+    pub stats: bool,
+    pub follow_symlinks: bool,
+    pub quiet: bool,
+    pub max_filesize: Option<u64>,
+    pub line_number_base: usize,
+    pub no_heading: bool,
+    pub preceded_by: Option<&'a str>,
+    pub followed_by: Option<&'a str>,
+    pub interactive: bool,
+    pub search_compressed: bool,
+    pub sort: SortBy,
+    pub query_file: Option<&'a str>,
+    pub context_symbol: bool,
+    pub unique: bool,
+    pub invert_symbol: bool,
+    pub only_matching: bool,
+    pub rev: Option<&'a str>,
+    pub max_columns: Option<usize>,
+    pub not_langs: Vec<Lang>,
+    pub threads: usize,
+    pub watch: bool,
+    pub line_number_width: Option<usize>,
+    pub ignore_case: bool,
+    pub allow_empty: bool,
+    pub list_symbols: bool,
+    pub tags: bool,
+    pub modified_after: Option<ModifiedTime>,
+    pub modified_before: Option<ModifiedTime>,
+    pub mmap: bool,
+    pub pcre: bool,
+    pub encoding: Option<&'static Encoding>,
+    pub detect_modeline: bool,
+    pub result_cap: usize,
+    pub context_function_body: bool,
+    pub search_archives: bool,
+    pub signatures: bool,
+    pub index: Option<&'a str>,
+    pub use_index: Option<&'a str>,
+    pub null: bool,
+    pub all_files: bool,
+    pub stats_json: bool,
+    pub diff: Option<&'a str>,
+    pub no_ignore: bool,
+    pub kinds: Vec<&'a str>,
+    pub group_by_symbol: bool,
+    pub after_context_symbol: usize,
+    pub warn_parse_errors: bool,
+    pub path_style: Option<PathStyle>,
+    pub count: bool,
+    pub count_matches: bool,
 }
 
 impl<'a> CommandArgs<'a> {
@@ -88,7 +692,7 @@ impl<'a> CommandArgs<'a> {
 
     *Use:*
 
-    ```rust
+    ```no_run
     use search_code::Options::cmd_args::CommandArgs;
     use search_code::Options::cmd_args::get_args_matches;
 
@@ -98,21 +702,191 @@ impl<'a> CommandArgs<'a> {
 
     */
     pub fn from_matches(matches: &'a ArgMatches) -> CommandArgs<'a> {
-        let path = matches.get_one::<String>("path or file").unwrap();
-        let key_to_search = matches.get_one::<String>("key_to_search").unwrap();
+        let path: Vec<&str> = matches
+            .get_many::<String>("path or file")
+            .unwrap()
+            .map(|v| v.as_str())
+            .collect();
+        let stdin = matches.get_flag("stdin") || path.contains(&"-");
+        let key_to_search = matches
+            .get_one::<String>("key_to_search")
+            .map_or("", |v| v.as_str());
         let is_symbol = matches.get_one::<String>("symbol").unwrap();
         let ignore = matches
             .get_many::<String>("ignore")
             .unwrap_or_default()
             .map(|v| v.as_str())
             .collect();
+        let exclude_symbol = matches
+            .get_many::<String>("exclude_symbol")
+            .unwrap_or_default()
+            .map(|v| v.as_str())
+            .collect();
+        let output = matches.get_one::<String>("output").cloned();
 
         CommandArgs {
             path,
             key_to_search,
-            lang: Lang::from_str(matches.get_one::<String>("language").unwrap()).unwrap(),
-            is_symbol: is_symbol == "true",
+            langs: matches.get_one::<Vec<Lang>>("language").unwrap().clone(),
+            is_symbol: is_symbol == "true"
+                || matches.get_flag("list_symbols")
+                || matches.get_flag("tags")
+                || matches.contains_id("index")
+                || matches.contains_id("use_index"),
             ignore,
+            output,
+            staged: matches.get_flag("staged"),
+            symbol_context: *matches.get_one::<usize>("symbol_context").unwrap(),
+            files_with_matches: matches.get_flag("files_with_matches"),
+            max_count: matches.get_one::<usize>("max_count").copied(),
+            max_total: matches.get_one::<usize>("max_total").copied(),
+            exclude_symbol,
+            auto: matches.get_flag("auto"),
+            stdin,
+            include: matches
+                .get_many::<String>("include")
+                .unwrap_or_default()
+                .cloned()
+                .collect(),
+            exclude: matches
+                .get_many::<String>("exclude")
+                .unwrap_or_default()
+                .cloned()
+                .collect(),
+            no_summary: matches.get_flag("no_summary"),
+            file_types: matches
+                .get_many::<String>("type")
+                .unwrap_or_default()
+                .map(|v| v.as_str())
+                .collect(),
+            color: ColorChoice::from_str(matches.get_one::<String>("color").unwrap()).unwrap(),
+            include_git: matches.get_flag("include_git"),
+            symbol_match: if matches.get_flag("fuzzy") {
+                SymbolMatch::Fuzzy
+            } else {
+                SymbolMatch::from_str(matches.get_one::<String>("symbol_match").unwrap()).unwrap()
+            },
+            format: if matches.get_flag("vimgrep") {
+                OutputFormat::Vimgrep
+            } else {
+                OutputFormat::from_str(matches.get_one::<String>("format").unwrap()).unwrap()
+            },
+            line_range: matches.get_one::<LineRange>("line_range").copied(),
+            list_files: matches.get_flag("list_files"),
+            parse_timeout: matches.get_one::<u64>("parse_timeout").copied(),
+            replace: matches.get_one::<String>("replace").map(|v| v.as_str()),
+            regex: matches.get_flag("regex"),
+            word: matches.get_flag("word"),
+            write: matches.get_flag("write"),
+            stats: matches.get_flag("stats"),
+            follow_symlinks: matches.get_flag("follow_symlinks"),
+            quiet: matches.get_flag("quiet"),
+            max_filesize: matches
+                .get_one::<u64>("max_filesize")
+                .copied()
+                .filter(|max_filesize| *max_filesize > 0),
+            line_number_base: matches
+                .get_one::<String>("line_number_base")
+                .unwrap()
+                .parse()
+                .unwrap(),
+            no_heading: matches.get_flag("no_heading"),
+            preceded_by: matches.get_one::<String>("preceded_by").map(|v| v.as_str()),
+            followed_by: matches.get_one::<String>("followed_by").map(|v| v.as_str()),
+            interactive: matches.get_flag("interactive"),
+            search_compressed: matches.get_flag("search_compressed"),
+            sort: SortBy::from_str(matches.get_one::<String>("sort").unwrap()).unwrap(),
+            query_file: matches.get_one::<String>("query_file").map(|v| v.as_str()),
+            context_symbol: matches.get_flag("context_symbol"),
+            unique: matches.get_flag("unique"),
+            invert_symbol: matches.get_flag("invert_symbol"),
+            only_matching: matches.get_flag("only_matching"),
+            rev: matches.get_one::<String>("rev").map(|v| v.as_str()),
+            max_columns: matches.get_one::<usize>("max_columns").copied(),
+            not_langs: matches
+                .get_one::<Vec<Lang>>("not_lang")
+                .cloned()
+                .unwrap_or_default(),
+            threads: *matches.get_one::<usize>("threads").unwrap(),
+            watch: matches.get_flag("watch"),
+            line_number_width: matches.get_one::<usize>("line_number_width").copied(),
+            ignore_case: matches.get_flag("ignore_case"),
+            allow_empty: matches.get_flag("allow_empty"),
+            list_symbols: matches.get_flag("list_symbols")
+                || matches.get_flag("tags")
+                || matches.contains_id("index"),
+            tags: matches.get_flag("tags"),
+            modified_after: matches.get_one::<ModifiedTime>("modified_after").copied(),
+            modified_before: matches.get_one::<ModifiedTime>("modified_before").copied(),
+            mmap: matches.get_flag("mmap"),
+            pcre: matches.get_flag("pcre"),
+            encoding: matches.get_one::<&Encoding>("encoding").copied(),
+            detect_modeline: matches.get_flag("detect_modeline"),
+            result_cap: matches
+                .get_one::<usize>("result_cap")
+                .copied()
+                .unwrap_or_default(),
+            context_function_body: matches.get_flag("context_function_body"),
+            search_archives: matches.get_flag("search_archives"),
+            signatures: matches.get_flag("signatures"),
+            index: matches.get_one::<String>("index").map(|v| v.as_str()),
+            use_index: matches.get_one::<String>("use_index").map(|v| v.as_str()),
+            null: matches.get_flag("null"),
+            all_files: matches.get_flag("all_files"),
+            stats_json: matches.get_flag("stats_json"),
+            diff: matches.get_one::<String>("diff").map(|v| v.as_str()),
+            no_ignore: matches.get_flag("no_ignore"),
+            kinds: matches
+                .get_many::<String>("kind")
+                .unwrap_or_default()
+                .map(|v| v.as_str())
+                .collect(),
+            group_by_symbol: matches.get_flag("group_by_symbol"),
+            after_context_symbol: *matches.get_one::<usize>("after_context_symbol").unwrap(),
+            warn_parse_errors: matches.get_flag("warn_parse_errors"),
+            path_style: matches
+                .get_one::<String>("path_style")
+                .map(|v| PathStyle::from_str(v).unwrap()),
+            count: matches.get_flag("count"),
+            count_matches: matches.get_flag("count_matches"),
+        }
+    }
+
+    /** Use the config as the defaults, the options set by the CLI flags are kept.
+
+    *Use:*
+
+    ```no_run
+    use search_code::Options::{cmd_args::{get_args_matches, CommandArgs}, config::Config};
+
+    let config = Config::load().unwrap();
+    let matches = get_args_matches();
+    let mut args = CommandArgs::from_matches(&matches);
+    args.apply_config(&config, &matches);
+    ```
+    */
+    pub fn apply_config(&mut self, config: &'a Config, matches: &ArgMatches) {
+        let is_default = |id: &str| {
+            matches!(
+                matches.value_source(id),
+                None | Some(ValueSource::DefaultValue)
+            )
+        };
+        if is_default("ignore") {
+            if let Some(ignore) = &config.ignore {
+                self.ignore = ignore.iter().map(|v| v.as_str()).collect();
+            }
+        }
+        if is_default("language") {
+            // the config lang is checked when it's loaded, see `Config::from_file`.
+            if let Some(Ok(langs)) = config.lang.as_deref().map(parse_langs) {
+                self.langs = langs;
+            }
+        }
+        if is_default("color") {
+            if let Some(color) = &config.color {
+                self.color = ColorChoice::from_str(color).unwrap_or_default();
+            }
         }
     }
 }
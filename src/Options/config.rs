@@ -0,0 +1,109 @@
+/*!
+    The config file to set the default options, named `.search_code.toml`.
+
+    The precedence is: CLI flag > local config > home config > built-in defaults.
+    The local config is in the current directory, the home config is in `$HOME`.
+
+    ```toml
+    ignore = ["node_modules", "target", "dist"]
+    lang = "rust"
+    color = "auto" # always, auto or never
+    text_extensions = ["rs", "md", "log"] # the text files searched with `-l any`
+    ```
+*/
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::Options::parse_langs;
+
+/// The name of the config file.
+pub const CONFIG_FILE_NAME: &str = ".search_code.toml";
+
+/// The default options read from the config file, `None` if not set.
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct Config {
+    pub ignore: Option<Vec<String>>,
+    pub lang: Option<String>,
+    pub color: Option<String>,
+    pub text_extensions: Option<Vec<String>>,
+}
+
+impl Config {
+    /** Load the home config and then the local config, the local one wins.
+
+    *NOTE:* the missing config files are just skipped.
+
+    *Return:* An `InvalidData` error if any config file can't be parsed.
+    */
+    pub fn load() -> Result<Config, io::Error> {
+        let mut config = Config::default();
+        let home_config =
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(CONFIG_FILE_NAME));
+        for path in home_config
+            .into_iter()
+            .chain([PathBuf::from(CONFIG_FILE_NAME)])
+        {
+            if let Some(file_config) = Config::from_file(&path)? {
+                config.merge(file_config);
+            }
+        }
+        Ok(config)
+    }
+
+    /// Read the config file, `None` if the file doesn't exist.
+    ///
+    /// *NOTE:* an unknown language in `lang` is an `InvalidData` error too.
+    pub fn from_file(path: &Path) -> Result<Option<Config>, io::Error> {
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let invalid = |err: &dyn std::fmt::Display| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {err}", path.display()),
+            )
+        };
+        let content = std::fs::read_to_string(path)?;
+        let config = Config::from_toml(&content).map_err(|err| invalid(&err))?;
+        if let Some(lang) = &config.lang {
+            parse_langs(lang).map_err(|err| invalid(&err))?;
+        }
+        Ok(Some(config))
+    }
+
+    /** Parse the config from the toml content.
+
+    ```rust
+    use search_code::Options::config::Config;
+
+    let config = Config::from_toml("ignore = [\"target\"]\nlang = \"rust\"").unwrap();
+    assert_eq!(config.ignore, Some(vec!["target".to_string()]));
+    assert_eq!(config.lang.as_deref(), Some("rust"));
+    assert!(config.color.is_none());
+    ```
+    */
+    pub fn from_toml(content: &str) -> Result<Config, toml::de::Error> {
+        toml::from_str(content)
+    }
+
+    /// Override the options set in `other`.
+    pub fn merge(&mut self, other: Config) {
+        if other.ignore.is_some() {
+            self.ignore = other.ignore;
+        }
+        if other.lang.is_some() {
+            self.lang = other.lang;
+        }
+        if other.color.is_some() {
+            self.color = other.color;
+        }
+        if other.text_extensions.is_some() {
+            self.text_extensions = other.text_extensions;
+        }
+    }
+}
@@ -22,10 +22,69 @@ pub const ABOUT: &str = "use to search some code line in a file.
 Will highlight the key and give you the line number of the line.
 ";
 
+/// The git files and dir, ignored by default.
+pub const GIT_FILES: [&str; 3] = [".git", ".gitignore", ".gitattributes"];
+
+/// The leading lines read to find the modeline, with `--detect-modeline`.
+pub const MODELINE_LINES: usize = 5;
+
 /// The program language extensions.
 pub const CPP_EXTENSIONS: [&str; 4] = ["cpp", "c", "cc", "cxx"];
 pub const RUST_EXTENSIONS: [&str; 2] = ["rs", "rust"];
 pub const PYTHON_EXTENSIONS: [&str; 1] = ["py"];
+pub const JAVA_EXTENSIONS: [&str; 1] = ["java"];
+pub const CSHARP_EXTENSIONS: [&str; 1] = ["cs"];
+pub const BASH_EXTENSIONS: [&str; 2] = ["sh", "bash"];
+pub const JSON_EXTENSIONS: [&str; 1] = ["json"];
+pub const KOTLIN_EXTENSIONS: [&str; 2] = ["kt", "kts"];
+pub const SWIFT_EXTENSIONS: [&str; 1] = ["swift"];
+pub const LUA_EXTENSIONS: [&str; 1] = ["lua"];
+pub const MARKDOWN_EXTENSIONS: [&str; 2] = ["md", "markdown"];
+pub const HCL_EXTENSIONS: [&str; 2] = ["tf", "hcl"];
+
+/// The extensions of the text files searched in `Lang::Any`, unless `--all-files`.
+pub const TEXT_EXTENSIONS: [&str; 74] = [
+    "c", "cc", "cpp", "cxx", "h", "hh", "hpp", "hxx", "rs", "rust", "py", "pyi", "java", "cs",
+    "kt", "kts", "swift", "lua", "go", "rb", "php", "pl", "scala", "dart", "zig", "hs", "ml", "ex",
+    "exs", "erl", "clj", "r", "jl", "nim", "sql", "sh", "bash", "zsh", "fish", "ps1", "bat", "js",
+    "jsx", "mjs", "cjs", "ts", "tsx", "vue", "svelte", "html", "htm", "css", "scss", "less", "xml",
+    "svg", "json", "toml", "yaml", "yml", "ini", "cfg", "conf", "env", "md", "markdown", "rst",
+    "txt", "tex", "proto", "graphql", "cmake", "tf", "hcl",
+];
+
+/// The lockfiles skipped in `Lang::Any` like the binaries, unless `--all-files`.
+pub const LOCK_FILE_NAMES: [&str; 8] = [
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+];
+
+/// The named file types for `--type`, the name -> the extensions.
+pub const FILE_TYPES: [(&str, &[&str]); 15] = [
+    (
+        "web",
+        &["html", "htm", "css", "scss", "js", "jsx", "ts", "tsx"],
+    ),
+    ("headers", &["h", "hh", "hpp", "hxx"]),
+    ("c", &["c", "h"]),
+    ("cpp", &["cpp", "cc", "cxx", "h", "hh", "hpp", "hxx"]),
+    ("python", &["py", "pyi"]),
+    ("rust", &["rs"]),
+    ("java", &["java"]),
+    ("csharp", &["cs"]),
+    ("kotlin", &["kt", "kts"]),
+    ("swift", &["swift"]),
+    ("lua", &["lua"]),
+    ("markdown", &["md", "markdown"]),
+    ("terraform", &["tf", "tfvars", "hcl"]),
+    ("shell", &["sh", "bash", "zsh"]),
+    ("config", &["toml", "yaml", "yml", "json", "ini"]),
+];
 
 /// The query to search for the key in the code.
 pub const CPP_MATCHES_QUERY: [&str; 3] = [
@@ -76,11 +135,250 @@ pub const RUST_MATCHES_QUERY: [&str; 4] = [
 "#,
     r#"
 ((enum_item
-    name:(type_identifier) @struct)
-    (#match? @struct ":?"))
+    name:(type_identifier) @enum)
+    (#match? @enum ":?"))
 "#,
     r#"
 ((identifier) @constant
  (#match? @constant ":?"))
 "#,
 ];
+
+pub const JAVA_MATCHES_QUERY: [&str; 4] = [
+    r#"
+((class_declaration
+    name:(identifier) @class)
+    (#match? @class ":?"))
+"#,
+    r#"
+((interface_declaration
+    name:(identifier) @interface)
+    (#match? @interface ":?"))
+"#,
+    r#"
+((method_declaration
+  name:(identifier) @method)
+            (#match? @method ":?"))
+"#,
+    r#"
+((enum_declaration
+    name:(identifier) @enum)
+    (#match? @enum ":?"))
+"#,
+];
+
+pub const CSHARP_MATCHES_QUERY: [&str; 4] = [
+    r#"
+((class_declaration
+    name:(identifier) @class)
+    (#match? @class ":?"))
+"#,
+    r#"
+((struct_declaration
+    name:(identifier) @struct)
+    (#match? @struct ":?"))
+"#,
+    r#"
+((interface_declaration
+    name:(identifier) @interface)
+    (#match? @interface ":?"))
+"#,
+    r#"
+((method_declaration
+    name:(identifier) @method)
+    (#match? @method ":?"))
+"#,
+];
+
+pub const BASH_MATCHES_QUERY: [&str; 1] = [r#"
+((function_definition
+    name:(word) @function)
+    (#match? @function ":?"))
+"#];
+
+/// The classes, the interfaces, the objects and the functions of kotlin.
+pub const KOTLIN_MATCHES_QUERY: [&str; 3] = [
+    r#"
+((class_declaration
+    name:(identifier) @class)
+    (#match? @class ":?"))
+"#,
+    r#"
+((object_declaration
+    name:(identifier) @object)
+    (#match? @object ":?"))
+"#,
+    r#"
+((function_declaration
+    name:(identifier) @function)
+    (#match? @function ":?"))
+"#,
+];
+
+/// The classes, the structs, the enums, the protocols and the functions of swift.
+pub const SWIFT_MATCHES_QUERY: [&str; 3] = [
+    r#"
+((class_declaration
+    name:(type_identifier) @class)
+    (#match? @class ":?"))
+"#,
+    r#"
+((protocol_declaration
+    name:(type_identifier) @protocol)
+    (#match? @protocol ":?"))
+"#,
+    r#"
+((function_declaration
+    name:(simple_identifier) @function)
+    (#match? @function ":?"))
+"#,
+];
+
+/// The functions of lua, like `function M.setup()` and `M.run = function()`, and the tables.
+pub const LUA_MATCHES_QUERY: [&str; 5] = [
+    r#"
+((function_declaration
+    name:(identifier) @function)
+    (#match? @function ":?"))
+"#,
+    r#"
+((function_declaration
+    name:[
+        (dot_index_expression field:(identifier) @function)
+        (method_index_expression method:(identifier) @function)
+    ])
+    (#match? @function ":?"))
+"#,
+    r#"
+((assignment_statement
+    (variable_list name:[
+        (identifier) @function
+        (dot_index_expression field:(identifier) @function)
+    ])
+    (expression_list value:(function_definition)))
+    (#match? @function ":?"))
+"#,
+    r#"
+((assignment_statement
+    (variable_list name:[
+        (identifier) @table
+        (dot_index_expression field:(identifier) @table)
+    ])
+    (expression_list value:(table_constructor)))
+    (#match? @table ":?"))
+"#,
+    r#"
+((field
+    name:(identifier) @function
+    value:(function_definition))
+    (#match? @function ":?"))
+"#,
+];
+
+/// The headings of markdown, the capture name is the level like `h2`.
+pub const MARKDOWN_MATCHES_QUERY: [&str; 8] = [
+    r#"
+((atx_heading (atx_h1_marker) heading_content:(inline) @h1)
+    (#match? @h1 ":?"))
+"#,
+    r#"
+((atx_heading (atx_h2_marker) heading_content:(inline) @h2)
+    (#match? @h2 ":?"))
+"#,
+    r#"
+((atx_heading (atx_h3_marker) heading_content:(inline) @h3)
+    (#match? @h3 ":?"))
+"#,
+    r#"
+((atx_heading (atx_h4_marker) heading_content:(inline) @h4)
+    (#match? @h4 ":?"))
+"#,
+    r#"
+((atx_heading (atx_h5_marker) heading_content:(inline) @h5)
+    (#match? @h5 ":?"))
+"#,
+    r#"
+((atx_heading (atx_h6_marker) heading_content:(inline) @h6)
+    (#match? @h6 ":?"))
+"#,
+    r#"
+((setext_heading heading_content:(paragraph (inline) @h1) (setext_h1_underline))
+    (#match? @h1 ":?"))
+"#,
+    r#"
+((setext_heading heading_content:(paragraph (inline) @h2) (setext_h2_underline))
+    (#match? @h2 ":?"))
+"#,
+];
+
+/// The labels of the HCL blocks, like `web` of `resource "aws_instance" "web" {}`.
+pub const HCL_MATCHES_QUERY: [&str; 2] = [
+    r#"
+((block
+    (string_lit (template_literal) @block))
+    (#match? @block ":?"))
+"#,
+    r#"
+((block
+    (_)
+    (identifier) @block)
+    (#match? @block ":?"))
+"#,
+];
+
+/// The keys of the JSON objects.
+pub const JSON_MATCHES_QUERY: [&str; 1] = [r#"
+((pair
+    key:(string (string_content) @key))
+    (#match? @key ":?"))
+"#];
+
+/// The captures of every identifier, not the definitions, skipped by `--list-symbols`.
+pub const LIST_SKIPPED_CAPTURES: [&str; 1] = ["constant"];
+
+/// The node kinds enclosing the code, like the functions and the classes.
+pub const CPP_SCOPE_KINDS: [&str; 4] = [
+    "function_definition",
+    "class_specifier",
+    "struct_specifier",
+    "namespace_definition",
+];
+pub const PYTHON_SCOPE_KINDS: [&str; 2] = ["function_definition", "class_definition"];
+pub const RUST_SCOPE_KINDS: [&str; 4] = ["function_item", "impl_item", "trait_item", "mod_item"];
+pub const JAVA_SCOPE_KINDS: [&str; 5] = [
+    "method_declaration",
+    "constructor_declaration",
+    "class_declaration",
+    "interface_declaration",
+    "enum_declaration",
+];
+pub const CSHARP_SCOPE_KINDS: [&str; 6] = [
+    "method_declaration",
+    "constructor_declaration",
+    "class_declaration",
+    "struct_declaration",
+    "interface_declaration",
+    "namespace_declaration",
+];
+pub const BASH_SCOPE_KINDS: [&str; 1] = ["function_definition"];
+pub const KOTLIN_SCOPE_KINDS: [&str; 3] = [
+    "function_declaration",
+    "class_declaration",
+    "object_declaration",
+];
+pub const SWIFT_SCOPE_KINDS: [&str; 3] = [
+    "function_declaration",
+    "class_declaration",
+    "protocol_declaration",
+];
+pub const LUA_SCOPE_KINDS: [&str; 2] = ["function_declaration", "function_definition"];
+pub const MARKDOWN_SCOPE_KINDS: [&str; 1] = ["section"];
+
+/// The scope kinds of the functions and the methods, printed by `--context-function-body`.
+pub const FUNCTION_SCOPE_KINDS: [&str; 5] = [
+    "function_item",
+    "function_definition",
+    "function_declaration",
+    "method_declaration",
+    "constructor_declaration",
+];
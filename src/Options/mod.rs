@@ -2,18 +2,35 @@
 
     The submodules are:
     - cmd_args: This module contains the command line arguments parsing logic.
+    - config: This module contains the config file to set the default options.
     - constants: This module contains the constants used in the project.
 */
 
 pub mod cmd_args;
+pub mod config;
 pub mod constants;
 
-use std::{path::Path, str::pattern::Pattern};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+    time::{Duration, SystemTime},
+};
 
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime};
 use tree_sitter::Language;
+use tree_sitter_bash;
+use tree_sitter_c_sharp;
 use tree_sitter_cpp;
+use tree_sitter_hcl;
+use tree_sitter_java;
+use tree_sitter_json;
+use tree_sitter_kotlin_ng;
+use tree_sitter_lua;
+use tree_sitter_md;
 use tree_sitter_python;
 use tree_sitter_rust;
+use tree_sitter_swift;
 
 /** Lang enum to define for which language the search will be done.
 
@@ -22,13 +39,22 @@ use tree_sitter_rust;
     - Cpp
     - Python
     - Rust
+    - Java
+    - CSharp
+    - Bash
+    - Kotlin
+    - Swift
+    - Lua
+    - Markdown: the symbols are the headings.
+    - Hcl: the symbols are the labels of the blocks, like the Terraform resources.
+    - Json: the symbols are the keys of the objects.
     - Any: This is the default value. just treat the file as raw text.
 
     *Use:*
 
     ```rust
+    use std::str::FromStr;
     use search_code::Options::Lang;
-    use tree_sitter::Query;
 
     let lang = Lang::from_str("cpp").unwrap();
     assert_eq!(lang, Lang::Cpp);
@@ -36,13 +62,22 @@ use tree_sitter_rust;
     *NOTE:* `search_query` is the query to search for the key in the code.
     This query is defined in the `constants.rs` file.
 */
-#[derive(Debug, Default, Clone, PartialEq)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
 pub enum Lang {
     C,
     Cpp,
     Python,
 
     Rust,
+    Java,
+    CSharp,
+    Bash,
+    Kotlin,
+    Swift,
+    Lua,
+    Markdown,
+    Hcl,
+    Json,
     #[default]
     Any,
 }
@@ -60,9 +95,12 @@ impl Lang {
     *NOTE:* This function will return `Err(Lang::Any)` if the language is not supported.
 
     ```rust
-    use search_code::Options::Lang;
-    let tree_sitter_lang = parser.get_lang().into_treesitter_language().unwrap();
-    Query::new(&tree_sitter_lang, search_query.replace(":?", key).as_str()).unwrap();
+    use search_code::Options::{get_query, Lang};
+    use tree_sitter::Query;
+
+    let tree_sitter_lang = Lang::Rust.into_treesitter_language().unwrap();
+    let search_query = get_query(&Lang::Rust)[0];
+    Query::new(&tree_sitter_lang, search_query.replace(":?", "main").as_str()).unwrap();
     ```
     */
     pub fn into_treesitter_language(&self) -> Result<Language, Lang> {
@@ -70,10 +108,202 @@ impl Lang {
             Lang::Cpp | Lang::C => Ok(tree_sitter_cpp::LANGUAGE.into()),
             Lang::Python => Ok(tree_sitter_python::LANGUAGE.into()),
             Lang::Rust => Ok(tree_sitter_rust::LANGUAGE.into()),
+            Lang::Java => Ok(tree_sitter_java::LANGUAGE.into()),
+            Lang::CSharp => Ok(tree_sitter_c_sharp::LANGUAGE.into()),
+            Lang::Bash => Ok(tree_sitter_bash::LANGUAGE.into()),
+            Lang::Kotlin => Ok(tree_sitter_kotlin_ng::LANGUAGE.into()),
+            Lang::Swift => Ok(tree_sitter_swift::LANGUAGE.into()),
+            Lang::Lua => Ok(tree_sitter_lua::LANGUAGE.into()),
+            Lang::Markdown => Ok(tree_sitter_md::LANGUAGE.into()),
+            Lang::Hcl => Ok(tree_sitter_hcl::LANGUAGE.into()),
+            Lang::Json => Ok(tree_sitter_json::LANGUAGE.into()),
             _ => Err(Lang::Any),
         }
     }
 
+    /// The languages with their own extensions and queries, `C` shares the ones of `Cpp`.
+    pub const SUPPORTED: [Lang; 12] = [
+        Lang::Cpp,
+        Lang::Python,
+        Lang::Rust,
+        Lang::Java,
+        Lang::CSharp,
+        Lang::Bash,
+        Lang::Kotlin,
+        Lang::Swift,
+        Lang::Lua,
+        Lang::Markdown,
+        Lang::Hcl,
+        Lang::Json,
+    ];
+
+    /** Get the language from the file extension, without the `.`.
+
+    *Return:* `None` if no supported language uses the extension.
+
+    ```rust
+    use search_code::Options::Lang;
+
+    assert_eq!(Lang::from_extension("rs"), Some(Lang::Rust));
+    assert_eq!(Lang::from_extension("c"), Some(Lang::Cpp));
+    assert_eq!(Lang::from_extension("cs"), Some(Lang::CSharp));
+    assert_eq!(Lang::from_extension("sh"), Some(Lang::Bash));
+    assert_eq!(Lang::from_extension("kts"), Some(Lang::Kotlin));
+    assert_eq!(Lang::from_extension("swift"), Some(Lang::Swift));
+    assert_eq!(Lang::from_extension("lua"), Some(Lang::Lua));
+    assert_eq!(Lang::from_extension("md"), Some(Lang::Markdown));
+    assert_eq!(Lang::from_extension("tf"), Some(Lang::Hcl));
+    assert_eq!(Lang::from_extension("json"), Some(Lang::Json));
+    assert_eq!(Lang::from_extension("txt"), None);
+    ```
+    */
+    pub fn from_extension(ext: &str) -> Option<Lang> {
+        Lang::SUPPORTED
+            .into_iter()
+            .find(|lang| lang.valid_extensions().contains(&ext))
+    }
+
+    /// Get the language from the extension of the path.
+    ///
+    /// *NOTE:* the file without extension is detected from its shebang line.
+    ///
+    /// *Return:* `None` if the language is unknown or not supported.
+    pub fn from_path(path: &Path) -> Option<Lang> {
+        Lang::detect(path, false)
+    }
+
+    /// Get the language from the extension of the path, or from the leading lines of the file.
+    ///
+    /// *NOTE:* with `modeline`, the file of unknown extension is detected too, from its shebang
+    /// or the modeline in the first `MODELINE_LINES` lines, like `-*- mode: rust -*-`.
+    /// Or only the file without extension is detected from its shebang line.
+    ///
+    /// *Return:* `None` if the language is unknown or not supported.
+    pub fn detect(path: &Path, modeline: bool) -> Option<Lang> {
+        match path.extension() {
+            Some(ext) => match ext.to_str().and_then(Lang::from_extension) {
+                None if modeline => Lang::from_leading_lines(path),
+                lang => lang,
+            },
+            None if modeline => Lang::from_leading_lines(path),
+            None => Lang::from_shebang_file(path),
+        }
+    }
+
+    /** Get the language from the Emacs or the Vim modeline.
+
+    ```rust
+    use search_code::Options::Lang;
+
+    assert_eq!(Lang::from_modeline("// -*- mode: rust; coding: utf-8 -*-"), Some(Lang::Rust));
+    assert_eq!(Lang::from_modeline("/* -*- C++ -*- */"), Some(Lang::Cpp));
+    assert_eq!(Lang::from_modeline("# vim: set ft=python ts=4:"), Some(Lang::Python));
+    assert_eq!(Lang::from_modeline("-- vi: filetype=lua"), Some(Lang::Lua));
+    assert_eq!(Lang::from_modeline("# -*- mode: org -*-"), None);
+    assert_eq!(Lang::from_modeline("let ft=python"), None);
+    ```
+    */
+    pub fn from_modeline(line: &str) -> Option<Lang> {
+        let name = emacs_mode(line).or_else(|| vim_filetype(line))?;
+        let lang = match name.to_lowercase().as_str() {
+            "c++" => Lang::Cpp,
+            "shell-script" | "zsh" => Lang::Bash,
+            name => obtain_lang(name),
+        };
+        (lang != Lang::Any).then_some(lang)
+    }
+
+    /// Read the first `MODELINE_LINES` lines of the file, and get the language from the shebang
+    /// or the modeline.
+    ///
+    /// *Return:* `None` if the file can't be read or declares no known language.
+    fn from_leading_lines(path: &Path) -> Option<Lang> {
+        let reader = BufReader::new(File::open(path).ok()?);
+        let mut lines = reader
+            .lines()
+            .take(constants::MODELINE_LINES)
+            .map_while(Result::ok);
+        let first = lines.next()?;
+        Lang::from_shebang(&first)
+            .or_else(|| Lang::from_modeline(&first))
+            .or_else(|| lines.find_map(|line| Lang::from_modeline(&line)))
+    }
+
+    /** Get the language from the shebang line, like `#!/bin/bash`.
+
+    ```rust
+    use search_code::Options::Lang;
+
+    assert_eq!(Lang::from_shebang("#!/bin/bash"), Some(Lang::Bash));
+    assert_eq!(Lang::from_shebang("#!/usr/bin/env python3 -u"), Some(Lang::Python));
+    assert_eq!(Lang::from_shebang("# just a comment"), None);
+    ```
+    */
+    pub fn from_shebang(line: &str) -> Option<Lang> {
+        let command = line.strip_prefix("#!")?.trim();
+        let mut words = command.split_whitespace();
+        let mut interpreter = words.next()?.rsplit('/').next()?;
+        if interpreter == "env" {
+            interpreter = words.find(|word| !word.starts_with('-'))?;
+        }
+        match interpreter.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.') {
+            "sh" | "bash" | "zsh" | "dash" | "ksh" => Some(Lang::Bash),
+            "python" => Some(Lang::Python),
+            _ => None,
+        }
+    }
+
+    /// Read the first line of the file and get the language from the shebang.
+    ///
+    /// *Return:* `None` if the file can't be read or has no known shebang.
+    pub fn from_shebang_file(path: &Path) -> Option<Lang> {
+        let mut line = String::new();
+        BufReader::new(File::open(path).ok()?)
+            .read_line(&mut line)
+            .ok()?;
+        Lang::from_shebang(&line)
+    }
+
+    /** Check if the file should be searched with the language.
+
+    *NOTE:* `Any` matches all the files, the others only match their own extensions.
+    The file without extension, like `Makefile` or `.gitignore`, is only searched in `Any`,
+    unless its shebang line declares the language.
+
+    ```rust
+    use std::path::Path;
+    use search_code::Options::Lang;
+
+    assert!(Lang::Rust.matches_path(Path::new("./src.d/main.rs")));
+    assert!(!Lang::Rust.matches_path(Path::new("./src.rs/README")));
+    assert!(Lang::Any.matches_path(Path::new("Makefile")));
+    assert!(!Lang::Cpp.matches_path(Path::new("./Makefile")));
+    assert!(!Lang::Bash.matches_path(Path::new("./Dockerfile")));
+    assert!(Lang::Any.matches_path(Path::new("./.gitignore")));
+    assert!(!Lang::Python.matches_path(Path::new("./.gitignore")));
+    assert!(!Lang::Rust.matches_path(Path::new("./src/.rs")));
+    ```
+    */
+    pub fn matches_path(&self, path: &Path) -> bool {
+        self.matches_file(path, false)
+    }
+
+    /// Check if the file should be searched with the language, see `matches_path`.
+    ///
+    /// *NOTE:* with `modeline`, the file of unknown extension matches the language declared by
+    /// its shebang or its modeline, see `detect`.
+    pub fn matches_file(&self, path: &Path, modeline: bool) -> bool {
+        if *self == Lang::Any {
+            return true;
+        }
+        match path.extension().map(|ext| ext.to_str()) {
+            Some(Some(ext)) if self.valid_extensions().contains(&ext) => true,
+            Some(Some(ext)) if Lang::from_extension(ext).is_some() => false,
+            Some(_) if !modeline => false,
+            _ => Lang::detect(path, modeline).as_ref() == Some(self),
+        }
+    }
+
     /// Get the valid extensions for the language.
     ///
     /// *Return:* The vector of the valid extensions. define in the `constants.rs` file.
@@ -82,17 +312,400 @@ impl Lang {
             Lang::Cpp | Lang::C => constants::CPP_EXTENSIONS.to_vec(),
             Lang::Python => constants::PYTHON_EXTENSIONS.to_vec(),
             Lang::Rust => constants::RUST_EXTENSIONS.to_vec(),
+            Lang::Java => constants::JAVA_EXTENSIONS.to_vec(),
+            Lang::CSharp => constants::CSHARP_EXTENSIONS.to_vec(),
+            Lang::Bash => constants::BASH_EXTENSIONS.to_vec(),
+            Lang::Kotlin => constants::KOTLIN_EXTENSIONS.to_vec(),
+            Lang::Swift => constants::SWIFT_EXTENSIONS.to_vec(),
+            Lang::Lua => constants::LUA_EXTENSIONS.to_vec(),
+            Lang::Markdown => constants::MARKDOWN_EXTENSIONS.to_vec(),
+            Lang::Hcl => constants::HCL_EXTENSIONS.to_vec(),
+            Lang::Json => constants::JSON_EXTENSIONS.to_vec(),
             _ => vec![],
         }
     }
 }
 
+/** Get the languages from the comma-separated names, like `rust,python`.
+
+    *NOTE:* the duplicates are dropped, and `any` makes it just `[Lang::Any]`,
+    which matches all the files.
+
+    *Return:* An error naming the first unknown language, so a typo doesn't search all the files.
+
+    ```rust
+    use search_code::Options::{parse_langs, Lang};
+
+    assert_eq!(parse_langs("rust, py,rs"), Ok(vec![Lang::Rust, Lang::Python]));
+    assert_eq!(parse_langs("rust,any"), Ok(vec![Lang::Any]));
+    assert!(parse_langs("rust,pyhton").is_err());
+    ```
+*/
+pub fn parse_langs(names: &str) -> Result<Vec<Lang>, String> {
+    let mut langs = Vec::new();
+    for name in names.split(',').map(str::trim) {
+        let lang = known_lang(name).ok_or_else(|| format!("unknown language `{name}`"))?;
+        if lang == Lang::Any {
+            return Ok(vec![Lang::Any]);
+        }
+        if !langs.contains(&lang) {
+            langs.push(lang);
+        }
+    }
+    Ok(langs)
+}
+
+/// Get the mode name from the Emacs modeline, like `-*- mode: rust -*-` or `-*- rust -*-`.
+fn emacs_mode(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once("-*-")?;
+    let (vars, _) = rest.split_once("-*-")?;
+    if !vars.contains(':') {
+        return Some(vars.trim()).filter(|mode| !mode.is_empty());
+    }
+    vars.split(';').find_map(|var| {
+        let (name, value) = var.split_once(':')?;
+        (name.trim().eq_ignore_ascii_case("mode")).then(|| value.trim())
+    })
+}
+
+/// Get the filetype from the Vim modeline, like `vim: set ft=python:` or `vi: filetype=lua`.
+fn vim_filetype(line: &str) -> Option<&str> {
+    let start = ["vim:", "vi:", "ex:"].into_iter().find_map(|marker| {
+        line.match_indices(marker)
+            .find(|(at, _)| line[..*at].ends_with(char::is_whitespace) || *at == 0)
+            .map(|(at, _)| at + marker.len())
+    })?;
+    line[start..]
+        .split(|c: char| c.is_whitespace() || c == ':')
+        .find_map(|option| {
+            option
+                .strip_prefix("ft=")
+                .or_else(|| option.strip_prefix("filetype="))
+        })
+}
+
 fn obtain_lang(lang: &str) -> Lang {
-    match lang {
+    known_lang(lang).unwrap_or_default()
+}
+
+/// The language of the name, `None` if the name is unknown.
+fn known_lang(lang: &str) -> Option<Lang> {
+    let lang = match lang {
         "c" | "cpp" | "cc" | "cxx" => Lang::Cpp,
         "python" | "Python" | "py" => Lang::Python,
         "rust" | "rs" => Lang::Rust,
-        _ => Lang::default(),
+        "java" => Lang::Java,
+        "csharp" | "cs" => Lang::CSharp,
+        "bash" | "sh" => Lang::Bash,
+        "kotlin" | "kt" => Lang::Kotlin,
+        "swift" => Lang::Swift,
+        "lua" => Lang::Lua,
+        "markdown" | "md" => Lang::Markdown,
+        "hcl" | "terraform" | "tf" => Lang::Hcl,
+        "json" => Lang::Json,
+        "any" => Lang::Any,
+        _ => return None,
+    };
+    Some(lang)
+}
+
+/** How the key matches the symbol names in the symbol search.
+
+    The key is put into the `:?` placeholder of the query, which is a regex.
+
+    ```rust
+    use std::str::FromStr;
+    use search_code::Options::SymbolMatch;
+
+    assert_eq!(SymbolMatch::Prefix.pattern("get_"), "^get_");
+    assert_eq!(SymbolMatch::Suffix.pattern("_impl"), "_impl$");
+    assert_eq!(SymbolMatch::Exact.pattern("a.b"), "^a\\\\.b$");
+    assert_eq!(SymbolMatch::Fuzzy.pattern("gtsym"), "");
+    assert_eq!(SymbolMatch::from_str("regex").unwrap(), SymbolMatch::Regex);
+    ```
+*/
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SymbolMatch {
+    /// The key is a regex, matches anywhere in the name. This is the default value.
+    #[default]
+    Regex,
+    /// The name is the key.
+    Exact,
+    /// The name starts with the key.
+    Prefix,
+    /// The name ends with the key.
+    Suffix,
+    /// The name fuzzy matches the key, like `gtsym` for `get_symbols`.
+    /// The query matches all the names, then they are scored against the key.
+    Fuzzy,
+}
+
+impl std::str::FromStr for SymbolMatch {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "regex" => Ok(SymbolMatch::Regex),
+            "exact" => Ok(SymbolMatch::Exact),
+            "prefix" => Ok(SymbolMatch::Prefix),
+            "suffix" => Ok(SymbolMatch::Suffix),
+            "fuzzy" => Ok(SymbolMatch::Fuzzy),
+            _ => Err(format!("unknown symbol match: {s}")),
+        }
+    }
+}
+
+impl SymbolMatch {
+    /** Get the regex to match the symbol names.
+
+    *NOTE:* in the regex match, the key that is not a valid regex is matched literally,
+    like `foo(`.
+
+    ```rust
+    use search_code::Options::SymbolMatch;
+
+    assert_eq!(SymbolMatch::Regex.regex("get_.*"), "get_.*");
+    assert_eq!(SymbolMatch::Regex.regex("foo("), "foo\\(");
+    assert_eq!(SymbolMatch::Exact.regex("a.b*"), "^a\\.b\\*$");
+    ```
+    */
+    pub fn regex(&self, key: &str) -> String {
+        match self {
+            SymbolMatch::Regex if regex::Regex::new(key).is_ok() => key.to_string(),
+            SymbolMatch::Regex => regex::escape(key),
+            SymbolMatch::Exact => format!("^{}$", regex::escape(key)),
+            SymbolMatch::Prefix => format!("^{}", regex::escape(key)),
+            SymbolMatch::Suffix => format!("{}$", regex::escape(key)),
+            SymbolMatch::Fuzzy => String::new(),
+        }
+    }
+
+    /** Get the pattern to replace the `:?` placeholder in the query.
+
+    *NOTE:* the regex is escaped to be put in the string of the query, so any key is safe,
+    even with the quotes or the `:?`.
+
+    ```rust
+    use search_code::Options::SymbolMatch;
+
+    assert_eq!(SymbolMatch::Regex.pattern("say\"hi"), "say\\\"hi");
+    assert_eq!(SymbolMatch::Regex.pattern("foo:?"), "foo:?");
+    assert_eq!(SymbolMatch::Prefix.pattern("foo:?"), "^foo:\\\\?");
+    ```
+    */
+    pub fn pattern(&self, key: &str) -> String {
+        self.regex(key).replace('\\', "\\\\").replace('"', "\\\"")
+    }
+}
+
+/** The 1-based inclusive range of the lines to search, like `100:200`.
+
+    The start or the end can be omitted, like `100:` or `:200`.
+
+    ```rust
+    use std::str::FromStr;
+    use search_code::Options::LineRange;
+
+    let range = LineRange::from_str("100:200").unwrap();
+    assert!(range.contains(100) && range.contains(200));
+    assert!(!range.contains(99) && !range.contains(201));
+    assert!(LineRange::from_str(":20").unwrap().contains(1));
+    assert!(LineRange::from_str("20:10").is_err());
+    ```
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl std::str::FromStr for LineRange {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid line range: {s}, expected START:END");
+        let (start, end) = s.split_once(':').ok_or_else(invalid)?;
+        let start = match start {
+            "" => 1,
+            start => start.parse().map_err(|_| invalid())?,
+        };
+        let end = match end {
+            "" => usize::MAX,
+            end => end.parse().map_err(|_| invalid())?,
+        };
+        if start == 0 || start > end {
+            return Err(invalid());
+        }
+        Ok(LineRange { start, end })
+    }
+}
+
+impl LineRange {
+    /// Check if the 1-based line number is in the range.
+    pub fn contains(&self, line_number: usize) -> bool {
+        (self.start..=self.end).contains(&line_number)
+    }
+}
+
+/** The time to compare the file modification time to, like `2024-05-01` or `7d`.
+
+    The accepted formats:
+    - the local date `2024-05-01`, at the start of the day.
+    - the local date time `2024-05-01T08:30:00` or `2024-05-01 08:30:00`.
+    - the RFC 3339 date time `2024-05-01T08:30:00+02:00`.
+    - the time ago from now, the number with the unit `s`, `m`, `h`, `d` or `w`, like `7d`.
+
+    ```rust
+    use std::{
+        str::FromStr,
+        time::{Duration, SystemTime},
+    };
+    use search_code::Options::ModifiedTime;
+
+    let week_ago = ModifiedTime::from_str("7d").unwrap().0;
+    let elapsed = SystemTime::now().duration_since(week_ago).unwrap();
+    assert!(elapsed >= Duration::from_secs(7 * 24 * 60 * 60));
+    assert!(ModifiedTime::from_str("2024-05-01").unwrap().0 < week_ago);
+    assert!(ModifiedTime::from_str("2024-05-01T08:30:00+02:00").is_ok());
+    assert!(ModifiedTime::from_str("7y").is_err());
+    ```
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModifiedTime(pub SystemTime);
+
+impl std::str::FromStr for ModifiedTime {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!("invalid time: {s}, expected a date like 2024-05-01 or the time ago like 7d")
+        };
+        if let Some((number, unit)) = s.split_at_checked(s.len().saturating_sub(1)) {
+            if let Ok(number) = number.parse::<u64>() {
+                let seconds = match unit {
+                    "s" => 1,
+                    "m" => 60,
+                    "h" => 60 * 60,
+                    "d" => 24 * 60 * 60,
+                    "w" => 7 * 24 * 60 * 60,
+                    _ => return Err(invalid()),
+                };
+                return SystemTime::now()
+                    .checked_sub(Duration::from_secs(number.saturating_mul(seconds)))
+                    .map(ModifiedTime)
+                    .ok_or_else(invalid);
+            }
+        }
+        if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+            return Ok(ModifiedTime(time.into()));
+        }
+        let local = NaiveDate::parse_from_str(s, "%Y-%m-%d")
+            .map(|date| date.and_time(NaiveTime::MIN))
+            .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S"))
+            .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S"))
+            .map_err(|_| invalid())?;
+        local
+            .and_local_timezone(Local)
+            .earliest()
+            .map(|time| ModifiedTime(time.into()))
+            .ok_or_else(invalid)
+    }
+}
+
+/// The format of the result.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    /// The colored text grouped by files. This is the default value.
+    #[default]
+    Text,
+    /// The JSON object, filename -> [{line, col, byte_offset, text, symbol}...].
+    Json,
+    /// The SARIF 2.1.0 log, one result for each found line, for the CI annotations.
+    Sarif,
+    /// The self-contained HTML report, a table of the files and the found lines of each file.
+    Html,
+    /// The newline-delimited JSON, one `{file, line, text}` object for each found line,
+    /// printed as soon as the file is searched.
+    JsonLines,
+    /// The uncolored `file:line:col:text` lines for the editors, one for each match.
+    Vimgrep,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "sarif" => Ok(OutputFormat::Sarif),
+            "html" => Ok(OutputFormat::Html),
+            "jsonl" => Ok(OutputFormat::JsonLines),
+            "vimgrep" => Ok(OutputFormat::Vimgrep),
+            _ => Err(format!("unknown output format: {s}")),
+        }
+    }
+}
+
+/// When to color the output.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum ColorChoice {
+    Always,
+    /// Color if the stdout is a terminal. This is the default value.
+    #[default]
+    Auto,
+    Never,
+}
+
+impl std::str::FromStr for ColorChoice {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "always" => Ok(ColorChoice::Always),
+            "auto" => Ok(ColorChoice::Auto),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!("unknown color choice: {s}")),
+        }
+    }
+}
+
+/// The order of the files in the result.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum SortBy {
+    /// By the path. This is the default value.
+    #[default]
+    Path,
+    /// The most recently modified first.
+    Mtime,
+    /// The most found lines first.
+    Matches,
+}
+
+impl std::str::FromStr for SortBy {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "path" => Ok(SortBy::Path),
+            "mtime" => Ok(SortBy::Mtime),
+            "matches" => Ok(SortBy::Matches),
+            _ => Err(format!("unknown sort: {s}")),
+        }
+    }
+}
+
+/// How the filenames are shown in the result, as scanned if not set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathStyle {
+    /// Relative to the current directory, the absolute one if outside it.
+    Relative,
+    /// The canonical absolute path.
+    Absolute,
+}
+
+impl std::str::FromStr for PathStyle {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "relative" => Ok(PathStyle::Relative),
+            "absolute" => Ok(PathStyle::Absolute),
+            _ => Err(format!("unknown path style: {s}")),
+        }
     }
 }
 
@@ -107,20 +720,177 @@ pub fn get_query(lang: &Lang) -> Vec<&'static str> {
         Lang::Cpp | Lang::C => constants::CPP_MATCHES_QUERY.to_vec(),
         Lang::Python => constants::PYTHON_MATCHES_QUERY.to_vec(),
         Lang::Rust => constants::RUST_MATCHES_QUERY.to_vec(),
+        Lang::Java => constants::JAVA_MATCHES_QUERY.to_vec(),
+        Lang::CSharp => constants::CSHARP_MATCHES_QUERY.to_vec(),
+        Lang::Bash => constants::BASH_MATCHES_QUERY.to_vec(),
+        Lang::Kotlin => constants::KOTLIN_MATCHES_QUERY.to_vec(),
+        Lang::Swift => constants::SWIFT_MATCHES_QUERY.to_vec(),
+        Lang::Lua => constants::LUA_MATCHES_QUERY.to_vec(),
+        Lang::Markdown => constants::MARKDOWN_MATCHES_QUERY.to_vec(),
+        Lang::Hcl => constants::HCL_MATCHES_QUERY.to_vec(),
+        Lang::Json => constants::JSON_MATCHES_QUERY.to_vec(),
         _ => vec![],
     }
 }
 
+/** Get the symbol kinds of the language, the capture names of its queries.
+
+*NOTE:* the kinds are sorted, an empty vector if the language is not supported.
+
+```rust
+use search_code::Options::{get_query_kinds, Lang};
+
+assert_eq!(get_query_kinds(&Lang::Rust), ["constant", "enum", "function", "struct"]);
+assert!(get_query_kinds(&Lang::Any).is_empty());
+```
+*/
+pub fn get_query_kinds(lang: &Lang) -> Vec<&'static str> {
+    let mut kinds: Vec<_> = get_query(lang)
+        .into_iter()
+        .flat_map(query_captures)
+        .collect();
+    kinds.sort_unstable();
+    kinds.dedup();
+    kinds
+}
+
+/** Get the queries of the language capturing any of the symbol kinds, see `get_query_kinds`.
+
+*NOTE:* all the queries if `kinds` is empty, the unknown kinds match no query.
+
+```rust
+use search_code::Options::{get_kind_query, get_query, Lang};
+
+let kinds = vec!["enum".to_string()];
+assert_eq!(get_kind_query(&Lang::Rust, &kinds).len(), 1);
+assert!(get_kind_query(&Lang::Rust, &kinds)[0].contains("enum_item"));
+assert_eq!(get_kind_query(&Lang::Rust, &[]), get_query(&Lang::Rust));
+```
+*/
+pub fn get_kind_query(lang: &Lang, kinds: &[String]) -> Vec<&'static str> {
+    get_query(lang)
+        .into_iter()
+        .filter(|query| {
+            kinds.is_empty()
+                || query_captures(query).any(|capture| kinds.iter().any(|v| v == capture))
+        })
+        .collect()
+}
+
+/// The capture names in the query, like `function` of `@function`, repeated if used again.
+fn query_captures(query: &str) -> impl Iterator<Item = &str> {
+    query.split('@').skip(1).filter_map(|capture| {
+        let end = capture
+            .find(|c: char| !c.is_alphanumeric() && c != '_' && c != '.')
+            .unwrap_or(capture.len());
+        (end > 0).then(|| &capture[..end])
+    })
+}
+
+/** Get the node kinds enclosing the code for the language, like the functions.
+
+*NOTE:* This function will return an empty vector if the language is not supported.
+
+*Return:* The vector of the node kinds. define in the `constants.rs` file.
+*/
+pub fn get_scope_kinds(lang: &Lang) -> Vec<&'static str> {
+    match lang {
+        Lang::Cpp | Lang::C => constants::CPP_SCOPE_KINDS.to_vec(),
+        Lang::Python => constants::PYTHON_SCOPE_KINDS.to_vec(),
+        Lang::Rust => constants::RUST_SCOPE_KINDS.to_vec(),
+        Lang::Java => constants::JAVA_SCOPE_KINDS.to_vec(),
+        Lang::CSharp => constants::CSHARP_SCOPE_KINDS.to_vec(),
+        Lang::Bash => constants::BASH_SCOPE_KINDS.to_vec(),
+        Lang::Kotlin => constants::KOTLIN_SCOPE_KINDS.to_vec(),
+        Lang::Swift => constants::SWIFT_SCOPE_KINDS.to_vec(),
+        Lang::Lua => constants::LUA_SCOPE_KINDS.to_vec(),
+        Lang::Markdown => constants::MARKDOWN_SCOPE_KINDS.to_vec(),
+        _ => vec![],
+    }
+}
+
+/** Get the extensions of the named file type, defined in the `constants.rs` file.
+
+*Return:* `None` if the file type is unknown.
+
+```rust
+use search_code::Options::type_extensions;
+
+assert!(type_extensions("headers").unwrap().contains(&"hpp"));
+assert!(type_extensions("nope").is_none());
+```
+*/
+pub fn type_extensions(name: &str) -> Option<&'static [&'static str]> {
+    constants::FILE_TYPES
+        .iter()
+        .find(|(type_name, _)| *type_name == name)
+        .map(|(_, extensions)| *extensions)
+}
+
+/** Normalize the path to compare the paths of any platform, without touching the file system.
+
+*NOTE:* the `\` is a separator too, the `.` and the empty parts are dropped, the `..` drops
+the part before it. The relative path starts with `./`, unless it starts with `..`.
+The absolute path keeps its root, `/` or the drive like `C:/`.
+
+```rust
+use search_code::Options::normalize_path;
+
+assert_eq!(normalize_path("./baz"), "./baz");
+assert_eq!(normalize_path("baz/"), "./baz");
+assert_eq!(normalize_path(".//foo/./bar"), "./foo/bar");
+assert_eq!(normalize_path("..\\foo"), "../foo");
+assert_eq!(normalize_path("a/../../foo"), "../foo");
+assert_eq!(normalize_path("C:\\bar"), "C:/bar");
+assert_eq!(normalize_path("/tmp/../bar/"), "/bar");
+assert_eq!(normalize_path(""), ".");
+```
+*/
+pub fn normalize_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    let bytes = path.as_bytes();
+    let (root, rest) = if let Some(rest) = path.strip_prefix('/') {
+        ("/", rest)
+    } else if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        (&path[..2], &path[2..])
+    } else {
+        ("", path.as_str())
+    };
+    let mut parts: Vec<&str> = Vec::new();
+    for part in rest.split('/') {
+        match part {
+            "" | "." => {}
+            ".." if parts.last().is_some_and(|last| *last != "..") => {
+                parts.pop();
+            }
+            // the parent of the root is the root.
+            ".." if !root.is_empty() => {}
+            part => parts.push(part),
+        }
+    }
+    let joined = parts.join("/");
+    match root {
+        "/" => format!("/{joined}"),
+        "" if parts.first() == Some(&"..") => joined,
+        "" if joined.is_empty() => ".".to_string(),
+        "" => format!("./{joined}"),
+        drive => format!("{drive}/{joined}"),
+    }
+}
+
 /// Check if any dir or file should be ignored.
 #[derive(Debug, Default)]
 pub struct IgnoreDir {
     dir_and_files: Vec<String>,
+    /// nothing is ignored, see `no_ignore`.
+    disabled: bool,
 }
 
 impl IgnoreDir {
     pub fn new() -> Self {
         IgnoreDir {
             dir_and_files: vec![],
+            disabled: false,
         }
     }
 
@@ -129,36 +899,81 @@ impl IgnoreDir {
         self.dir_and_files = dir_and_files;
     }
 
-    /// Fix the relative dir or file. to add the `./` at the start.
+    /** Fix the relative dir or file to start with `./`, and normalize all of them,
+    see `normalize_path`.
+
+    ```rust
+    use search_code::Options::IgnoreDir;
+
+    let mut ignore_dir = IgnoreDir::new();
+    ignore_dir.set_ignores(vec!["target/".to_string(), ".//dist".to_string()]);
+    ignore_dir.relative_dir_fix();
+    assert!(ignore_dir.is_ignore("./target"));
+    assert!(ignore_dir.is_ignore(".\\dist"));
+    assert!(!ignore_dir.is_ignore("./src/target"));
+    ```
+    */
     pub fn relative_dir_fix(&mut self) {
-        self.dir_and_files.iter_mut().for_each(|v| {
-            if Path::new(v).is_relative() && !v.starts_with("./") {
-                v.insert_str(0, "./");
-            }
-        });
+        self.dir_and_files
+            .iter_mut()
+            .for_each(|v| *v = normalize_path(v));
     }
 
-    /// Check if the dir should be ignored.
+    /// Check if the dir should be ignored, the dir is normalized like the ignored ones.
     pub fn is_ignore(&self, dir: &str) -> bool {
-        self.dir_and_files.contains(&dir.to_string())
+        !self.disabled && self.dir_and_files.contains(&normalize_path(dir))
     }
 
-    /// Exclude the git dir.
+    /** Disable all the ignore rules, the set ones and the git dir, to scan the full tree.
+
+    ```rust
+    use search_code::Options::IgnoreDir;
+
+    let mut ignore_dir = IgnoreDir::new();
+    ignore_dir.set_ignores(vec!["target".to_string()]);
+    ignore_dir.no_ignore();
+    ignore_dir.ignore_git();
+    ignore_dir.relative_dir_fix();
+    assert!(!ignore_dir.is_ignore("./target"));
+    assert!(!ignore_dir.is_ignore("./.git"));
+    ```
+    */
+    pub fn no_ignore(&mut self) {
+        self.disabled = true;
+        self.dir_and_files.clear();
+    }
+
+    /** Exclude the git dir, undo the `ignore_git`.
+
+    ```rust
+    use search_code::Options::IgnoreDir;
+
+    let mut ignore_dir = IgnoreDir::new();
+    ignore_dir.set_ignores(vec!["target".to_string()]);
+    ignore_dir.ignore_git();
+    ignore_dir.relative_dir_fix();
+    assert!(ignore_dir.is_ignore("./.git"));
+    assert!(ignore_dir.is_ignore("./.gitignore"));
+
+    ignore_dir.exclude_git();
+    assert!(!ignore_dir.is_ignore("./.git"));
+    assert!(!ignore_dir.is_ignore("./.gitattributes"));
+    assert!(ignore_dir.is_ignore("./target"));
+    ```
+    */
     pub fn exclude_git(&mut self) {
         self.dir_and_files
-            .iter_mut()
-            .filter(|v| "git".is_contained_in(v))
-            .for_each(|v| {
-                v.pop();
-            });
+            .retain(|v| !constants::GIT_FILES.contains(&v.trim_start_matches("./")));
     }
 
     /// Ignore the git dir.
+    ///
+    /// *NOTE:* skipped after `no_ignore`.
     pub fn ignore_git(&mut self) {
-        self.dir_and_files.extend(
-            [".git", ".gitignore", ".gitattributes"]
-                .iter()
-                .map(|v| v.to_string()),
-        );
+        if self.disabled {
+            return;
+        }
+        self.dir_and_files
+            .extend(constants::GIT_FILES.iter().map(|v| v.to_string()));
     }
 }
@@ -0,0 +1,117 @@
+/*! Read the files in the archives, like `dist.zip` or `release.tar.gz`.
+
+    Used by the `--search-archives` mode to search the files without extracting them.
+*/
+
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, BufReader, Read},
+    path::Path,
+};
+
+use flate2::read::GzDecoder;
+
+/// entry path -> content
+pub type ArchiveFiles = BTreeMap<String, String>;
+
+/// The kinds of the supported archives.
+enum ArchiveKind {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl ArchiveKind {
+    /// Get the kind of the archive from the file name, ignoring the case.
+    fn from_path(path: &Path) -> Option<ArchiveKind> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveKind::Tar)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveKind::TarGz)
+        } else {
+            None
+        }
+    }
+}
+
+/** Check if the file is a supported archive, `.zip`, `.tar`, `.tar.gz` or `.tgz`.
+
+```rust
+use std::path::Path;
+use search_code::archive::is_archive;
+
+assert!(is_archive(Path::new("dist/app.zip")));
+assert!(is_archive(Path::new("release.TAR.GZ")));
+assert!(!is_archive(Path::new("access.log.gz")));
+```
+*/
+pub fn is_archive(path: &Path) -> bool {
+    ArchiveKind::from_path(path).is_some()
+}
+
+/** Get the files in the archive.
+
+*NOTE:* the dirs, the binary and the non-UTF-8 files are skipped.
+
+*Return:* entry path -> the content, or `io::ErrorKind::InvalidInput` if not an archive.
+*/
+pub fn archive_files(path: &Path) -> Result<ArchiveFiles, io::Error> {
+    let kind = ArchiveKind::from_path(path)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not an archive"))?;
+    let file = BufReader::new(File::open(path)?);
+    match kind {
+        ArchiveKind::Zip => zip_files(file),
+        ArchiveKind::Tar => tar_files(file),
+        ArchiveKind::TarGz => tar_files(GzDecoder::new(file)),
+    }
+}
+
+/// Read the files of the zip archive, see `archive_files`.
+fn zip_files(file: BufReader<File>) -> Result<ArchiveFiles, io::Error> {
+    let mut archive = zip::ZipArchive::new(file)?;
+    let mut files = BTreeMap::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name()?.into_owned();
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes)?;
+        if let Some(content) = text_content(bytes) {
+            files.insert(name, content);
+        }
+    }
+    Ok(files)
+}
+
+/// Read the files of the tar archive, see `archive_files`.
+fn tar_files(reader: impl Read) -> Result<ArchiveFiles, io::Error> {
+    let mut archive = tar::Archive::new(reader);
+    let mut files = BTreeMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes)?;
+        if let Some(content) = text_content(bytes) {
+            files.insert(name, content);
+        }
+    }
+    Ok(files)
+}
+
+/// The content of the text file, `None` if it is binary or not UTF-8.
+fn text_content(bytes: Vec<u8>) -> Option<String> {
+    if bytes.contains(&0) {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
@@ -0,0 +1,301 @@
+/*! Read the lines added in a git diff, and the files at a git revision.
+
+    Used by the `--staged` mode to search only the lines you are about to commit,
+    by the `--diff` mode to search only the lines changed since a git ref,
+    and by the `--rev` mode to search the files of a past commit.
+*/
+
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+use git2::{
+    Diff, DiffLineType, DiffOptions, ObjectType, Oid, Repository, TreeWalkMode, TreeWalkResult,
+};
+
+/// filename -> [(line_number, line)...]
+pub type AddedLines = BTreeMap<String, Vec<(usize, String)>>;
+
+/** Get the lines added in the git staging area, since `HEAD`.
+
+*NOTE:* the repository is found from the current directory,
+the filenames are relative to the top of its working tree, like `git diff --cached`.
+
+*Return:* The added lines with their new-file line numbers.
+*/
+pub fn staged_added_lines() -> Result<AddedLines, io::Error> {
+    let repo = Repo::open()?;
+    // no tree before the first commit, all the staged lines are added.
+    let head = repo.repo.head().and_then(|head| head.peel_to_tree()).ok();
+    let diff = repo
+        .repo
+        .diff_tree_to_index(head.as_ref(), None, Some(&mut diff_options()))
+        .map_err(io::Error::other)?;
+    added_lines(&diff).map_err(io::Error::other)
+}
+
+/// Read the staged file of `staged_added_lines` from the git index.
+pub fn staged_content(path: &str) -> Result<Vec<u8>, io::Error> {
+    let repo = Repo::open()?;
+    let index = repo.repo.index().map_err(io::Error::other)?;
+    let entry = index
+        .get_path(Path::new(path), 0)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{path} is not staged")))?;
+    let blob = repo.repo.find_blob(entry.id).map_err(io::Error::other)?;
+    Ok(blob.content().to_vec())
+}
+
+/** Get the lines added or modified in the working tree since the git ref, like `main`.
+
+*NOTE:* the repository is found from the current directory, only the files
+under the paths are diffed, and the filenames are relative to the current directory.
+The paths out of the working tree have no lines, like in `rev_files`.
+
+*Return:* The added lines with their new-file line numbers.
+*/
+pub fn ref_added_lines(rev: &str, paths: &[&str]) -> Result<AddedLines, io::Error> {
+    let repo = Repo::open()?;
+    let tree = repo
+        .repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_tree())
+        .map_err(io::Error::other)?;
+    let mut options = diff_options();
+    let pathspecs = repo.pathspecs(paths);
+    // all the paths are out of the working tree, not the whole tree.
+    if pathspecs.is_empty() {
+        return Ok(AddedLines::new());
+    }
+    // an empty pathspec is the whole tree, the same as no pathspec.
+    if !pathspecs.iter().any(String::is_empty) {
+        for spec in &pathspecs {
+            options.pathspec(spec);
+        }
+    }
+    let diff = repo
+        .repo
+        .diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))
+        .map_err(io::Error::other)?;
+    let added = added_lines(&diff).map_err(io::Error::other)?;
+    Ok(added
+        .into_iter()
+        .map(|(filename, lines)| (repo.relative(&filename), lines))
+        .collect())
+}
+
+/// The options to diff the changed lines only, the paths are literal, not globs.
+fn diff_options() -> DiffOptions {
+    let mut options = DiffOptions::new();
+    options.context_lines(0).disable_pathspec_match(true);
+    options
+}
+
+/** Collect the added lines of the diff hunks, the binary files have none.
+
+*Return:* filename -> [(line_number, line)...], the filename is relative to the top
+of the working tree, and the line number is in the new file.
+
+```rust
+use git2::{DiffOptions, Repository, Signature};
+use search_code::diff::added_lines;
+
+let dir = tempfile::tempdir().unwrap();
+let repo = Repository::init(dir.path()).unwrap();
+std::fs::write(dir.path().join("a.txt"), "one\ntwo\nthree\nfour\n").unwrap();
+let mut index = repo.index().unwrap();
+index.add_path("a.txt".as_ref()).unwrap();
+let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+let signature = Signature::now("search_code", "search_code@example.com").unwrap();
+repo.commit(Some("HEAD"), &signature, &signature, "init", &tree, &[]).unwrap();
+
+// a line before the first one `@@ -0,0 +1 @@`, a changed line `@@ -2 +3 @@`
+// with the counts omitted, and a deleted line `@@ -4 +4,0 @@` adding nothing.
+std::fs::write(dir.path().join("a.txt"), "zero\none\nTWO\nthree\n").unwrap();
+let mut options = DiffOptions::new();
+options.context_lines(0);
+let diff = repo.diff_tree_to_workdir(Some(&tree), Some(&mut options)).unwrap();
+assert_eq!(
+    added_lines(&diff).unwrap()["a.txt"],
+    vec![(1, "zero".to_string()), (3, "TWO".to_string())]
+);
+```
+*/
+pub fn added_lines(diff: &Diff) -> Result<AddedLines, git2::Error> {
+    let mut added = BTreeMap::new();
+    diff.foreach(
+        &mut |_, _| true,
+        None,
+        None,
+        Some(&mut |delta, _, line| {
+            let filename = delta.new_file().path().and_then(Path::to_str);
+            if let (Some(filename), Some(line_number)) = (filename, line.new_lineno()) {
+                if line.origin_value() == DiffLineType::Addition {
+                    let text = String::from_utf8_lossy(line.content());
+                    let text = text
+                        .strip_suffix('\n')
+                        .map(|v| v.strip_suffix('\r').unwrap_or(v))
+                        .unwrap_or(&text);
+                    added
+                        .entry(filename.to_string())
+                        .or_insert_with(Vec::new)
+                        .push((line_number as usize, text.to_string()));
+                }
+            }
+            true
+        }),
+    )?;
+    Ok(added)
+}
+
+/** Get the files under the paths at the git revision, like `HEAD~3` or a commit hash.
+
+*NOTE:* the repository is found from the current directory, the paths and
+the filenames are relative to it. Each blob is read when the file is reached,
+the binary and the non-UTF-8 files are skipped, so are the symlinks and the submodules.
+
+*Return:* the iterator of the filename and the content at the revision, sorted by filename.
+*/
+pub fn rev_files(rev: &str, paths: &[&str]) -> Result<RevFiles, io::Error> {
+    let repo = Repo::open()?;
+    let pathspecs = repo.pathspecs(paths);
+    let mut blobs = Vec::new();
+    // the tree borrows the repository, which is moved into the files after.
+    {
+        let tree = repo
+            .repo
+            .revparse_single(rev)
+            .and_then(|object| object.peel_to_tree())
+            .map_err(io::Error::other)?;
+        tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+            let is_file =
+                entry.kind() == Some(ObjectType::Blob) && entry.filemode() != SYMLINK_MODE;
+            if let Some(name) = entry.name().filter(|_| is_file) {
+                let path = format!("{root}{name}");
+                if pathspecs.iter().any(|spec| in_pathspec(&path, spec)) {
+                    blobs.push((repo.relative(&path), entry.id()));
+                }
+            }
+            TreeWalkResult::Ok
+        })
+        .map_err(io::Error::other)?;
+    }
+    blobs.sort();
+    Ok(RevFiles {
+        repo: repo.repo,
+        blobs: blobs.into_iter(),
+    })
+}
+
+/// The files at a git revision, see `rev_files`.
+///
+/// *NOTE:* only one file is read into memory at a time.
+pub struct RevFiles {
+    repo: Repository,
+    /// the filenames relative to the current directory, and their blobs.
+    blobs: std::vec::IntoIter<(String, Oid)>,
+}
+
+impl Iterator for RevFiles {
+    /// the filename and the content.
+    type Item = (String, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (filename, oid) in self.blobs.by_ref() {
+            let Ok(blob) = self.repo.find_blob(oid) else {
+                continue;
+            };
+            if blob.content().contains(&0) {
+                continue;
+            }
+            if let Ok(content) = std::str::from_utf8(blob.content()) {
+                return Some((filename, content.to_string()));
+            }
+        }
+        None
+    }
+}
+
+/// The git file mode of a symlink.
+const SYMLINK_MODE: i32 = 0o120000;
+
+/// The repository found from the current directory.
+struct Repo {
+    repo: Repository,
+    /// the working tree, canonicalized.
+    workdir: PathBuf,
+    /// the current directory relative to the working tree, empty at the top.
+    cwd: PathBuf,
+}
+
+impl Repo {
+    fn open() -> Result<Repo, io::Error> {
+        let repo = Repository::discover(".").map_err(io::Error::other)?;
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| io::Error::other("the repository has no working tree"))?
+            .canonicalize()?;
+        let cwd = std::env::current_dir()?
+            .canonicalize()?
+            .strip_prefix(&workdir)
+            .map_err(io::Error::other)?
+            .to_path_buf();
+        Ok(Repo { repo, workdir, cwd })
+    }
+
+    /// The paths relative to the top of the working tree, `/` separated,
+    /// the current directory if no path. The paths out of the working tree are dropped.
+    fn pathspecs(&self, paths: &[&str]) -> Vec<String> {
+        let paths = if paths.is_empty() { &["."][..] } else { paths };
+        paths
+            .iter()
+            .filter_map(|path| {
+                let path = Path::new(path);
+                let path = match path.strip_prefix(&self.workdir) {
+                    Ok(path) => path.to_path_buf(),
+                    Err(_) => self.cwd.join(path),
+                };
+                normalize(&path)
+            })
+            .collect()
+    }
+
+    /// The path relative to the current directory, of the one relative to the top.
+    fn relative(&self, path: &str) -> String {
+        let cwd: Vec<_> = self
+            .cwd
+            .iter()
+            .filter_map(|component| component.to_str())
+            .collect();
+        let path: Vec<_> = path.split('/').collect();
+        let common = cwd.iter().zip(&path).take_while(|(a, b)| a == b).count();
+        let mut relative = vec![".."; cwd.len() - common];
+        relative.extend(&path[common..]);
+        relative.join("/")
+    }
+}
+
+/// Resolve the `.` and the `..` in the relative path, `None` if it goes above the top.
+fn normalize(path: &Path) -> Option<String> {
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => components.push(name.to_str()?),
+            Component::ParentDir => {
+                components.pop()?;
+            }
+            Component::CurDir => {}
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(components.join("/"))
+}
+
+/// Check if the path is the pathspec or under it, the empty pathspec is the whole tree.
+fn in_pathspec(path: &str, spec: &str) -> bool {
+    spec.is_empty()
+        || path
+            .strip_prefix(spec)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
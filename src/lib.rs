@@ -1,5 +1,13 @@
 #![allow(non_snake_case)]
-#![feature(pattern)]
 pub mod Options;
 
 pub mod parser_lang;
+
+pub mod archive;
+
+pub mod diff;
+
+pub mod search;
+
+pub mod tui;
+pub mod watch;
@@ -1,244 +1,1600 @@
 #![allow(non_snake_case)]
 
 use colored::Colorize;
-use indicatif::{ProgressBar, ProgressStyle};
+use encoding_rs::Encoding;
+use globset::GlobSet;
+use prettytable::{row, Table};
 use search_code::{
-    parser_lang::SpecifyParser,
+    parser_lang::decode,
+    search::{
+        build_globset,
+        highlight::{highlight_with, search_one_line},
+        index::SymbolIndex,
+        matcher::KeyMatcher,
+        replace::write_replaced,
+        truncate_total, FindLines, FoundLine, LangStats, SearchCode, SearchOptions,
+    },
+    tui,
+    watch::Watcher,
     Options::{
         cmd_args::{get_args_matches, CommandArgs},
-        get_query, IgnoreDir, Lang,
+        config::Config,
+        get_query_kinds, type_extensions, ColorChoice, IgnoreDir, Lang, OutputFormat, PathStyle,
+        SortBy, SymbolMatch,
     },
 };
-
-use tree_sitter::{Query, QueryCursor};
+use serde::{Serialize, Serializer};
 
 use std::{
-    collections::HashMap,
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
     fs::File,
     io,
-    io::{BufRead, BufReader},
-    path::Path,
-    time::Duration,
+    io::{BufWriter, IsTerminal, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 
 fn main() {
+    let start = Instant::now();
+    let config = Config::load().unwrap_or_else(|err| {
+        eprintln!("{}", format!("Can't load the config: {err}").red());
+        std::process::exit(1);
+    });
     let matches = get_args_matches();
-    let args = CommandArgs::from_matches(&matches);
+    let mut args = CommandArgs::from_matches(&matches);
+    args.apply_config(&config, &matches);
+    match args.color {
+        ColorChoice::Always => colored::control::set_override(true),
+        ColorChoice::Never => colored::control::set_override(false),
+        ColorChoice::Auto => colored::control::set_override(io::stdout().is_terminal()),
+    }
+    if let Err(err) = rayon::ThreadPoolBuilder::new()
+        .num_threads(args.threads)
+        .build_global()
+    {
+        eprintln!("{}", format!("Can't build the thread pool: {err}").red());
+        std::process::exit(1);
+    }
     let mut ignore_dir = IgnoreDir::new();
     ignore_dir.set_ignores(args.ignore.iter().map(|dir| dir.to_string()).collect());
     ignore_dir.ignore_git();
     ignore_dir.relative_dir_fix();
+    if args.include_git {
+        ignore_dir.exclude_git();
+    }
+    if args.no_ignore {
+        ignore_dir.no_ignore();
+    }
 
-    let mut search = SearchCode::build(&args.lang, ignore_dir);
+    // the empty key matches every line, the inverted empty symbol key lists all the symbols.
+    let allowed = args.allow_empty || args.list_symbols || (args.is_symbol && args.invert_symbol);
+    if args.key_to_search.trim().is_empty() && !allowed {
+        eprintln!(
+            "{}",
+            "The key is empty, it matches every line. Use --allow-empty to search it anyway".red()
+        );
+        std::process::exit(1);
+    }
 
-    if Path::new(args.path).is_dir() {
-        if args.is_symbol {
-            print_result(
-                search
-                    .walk_dir(Path::new(args.path), args.key_to_search, &get_symbols)
-                    .expect("failed, Maybe contain invalid utf-8 characters."),
-                args.key_to_search,
-                colored::Color::Red,
-            );
+    let result_cap = result_cap(&args);
+    // one more line than the cap is found, to know if the search is stopped by the cap.
+    let max_total = [args.max_total, result_cap.map(|cap| cap + 1)]
+        .into_iter()
+        .flatten()
+        .min();
+    let mut search = SearchCode::build_langs(&args.langs, ignore_dir);
+    let options = SearchOptions {
+        is_symbol: args.is_symbol,
+        // one found line is enough to know the file matches, the rest of the file isn't read.
+        // not in the normal and the count modes, all the lines are needed there.
+        max_count: [
+            (args.files_with_matches || args.quiet).then_some(1),
+            args.max_count,
+            max_total,
+        ]
+        .into_iter()
+        .flatten()
+        .min(),
+        max_total,
+        exclude_symbol: args.exclude_symbol.iter().map(|v| v.to_string()).collect(),
+        auto: args.auto,
+        include: globset_or_exit(&args.include),
+        exclude: globset_or_exit(&args.exclude),
+        type_extensions: args
+            .file_types
+            .iter()
+            .flat_map(|name| {
+                type_extensions(name).unwrap_or_else(|| {
+                    eprintln!("{}", format!("Unknown file type: {name}").red());
+                    std::process::exit(1);
+                })
+            })
+            .copied()
+            .collect(),
+        symbol_match: args.symbol_match,
+        line_range: args.line_range,
+        parse_timeout: args.parse_timeout.map(Duration::from_millis),
+        regex: args.regex,
+        word: args.word,
+        ignore_case: args.ignore_case,
+        follow_symlinks: args.follow_symlinks,
+        // no flag for it, only the library searches limit the depth.
+        max_depth: None,
+        quiet: args.quiet,
+        max_filesize: args.max_filesize,
+        preceded_by: args.preceded_by.map(str::to_string),
+        followed_by: args.followed_by.map(str::to_string),
+        search_compressed: args.search_compressed,
+        context_symbol: args.context_symbol || args.group_by_symbol,
+        context_function_body: args.context_function_body,
+        invert_symbol: args.invert_symbol,
+        list_symbols: args.list_symbols,
+        modified_after: args.modified_after.map(|time| time.0),
+        modified_before: args.modified_before.map(|time| time.0),
+        mmap: args.mmap,
+        pcre: args.pcre,
+        encoding: args.encoding,
+        detect_modeline: args.detect_modeline,
+        search_archives: args.search_archives,
+        signatures: args.signatures,
+        after_context_symbol: args.after_context_symbol,
+        warn_parse_errors: args.warn_parse_errors,
+        all_files: args.all_files,
+        text_extensions: config.text_extensions.clone().unwrap_or_default(),
+        // only the watch mode searches the same files again.
+        cache_asts: args.watch,
+        not_langs: args.not_langs.clone(),
+        kinds: args.kinds.iter().map(|v| v.to_string()).collect(),
+        query: args.query_file.map(|query_file| {
+            std::fs::read_to_string(query_file).unwrap_or_else(|err| {
+                eprintln!(
+                    "{}",
+                    format!("Can't read the query file {query_file}: {err}").red()
+                );
+                std::process::exit(1);
+            })
+        }),
+    };
+    let matcher = options
+        .key_matcher(args.key_to_search)
+        .unwrap_or_else(|err| {
+            eprintln!("{}", format!("Invalid regex: {err}").red());
+            if !args.pcre && err.to_string().contains("not supported") {
+                eprintln!(
+                    "{}",
+                    "Use --pcre for the lookaround and the backreferences".yellow()
+                );
+            }
+            std::process::exit(1);
+        });
+    search.set_options(options);
+    if args.is_symbol && args.query_file.is_none() {
+        warn_unknown_kinds(&args);
+    }
+    let streamed = Arc::new(AtomicUsize::new(0));
+    if streams_json_lines(&args) {
+        let (base, files_only) = (args.line_number_base, args.files_with_matches);
+        let (path_style, cwd) = (args.path_style, current_dir());
+        let cap = result_cap.unwrap_or(usize::MAX);
+        let streamed = Arc::clone(&streamed);
+        search.set_visitor(Box::new(move |filename, find| {
+            let before = streamed.fetch_add(find.len(), Ordering::Relaxed);
+            let find = &find[..find.len().min(cap.saturating_sub(before))];
+            if !find.is_empty() {
+                let filename = styled_path(filename, path_style, cwd.as_deref());
+                print!("{}", json_lines(&filename, find, base, files_only));
+            }
+        }));
+    }
+
+    if args.stdin {
+        search.set_pathes(
+            io::stdin()
+                .lines()
+                .map_while(Result::ok)
+                .filter(|path| !path.trim().is_empty())
+                .collect(),
+        );
+    }
+
+    if args.list_files {
+        let files = if args.stdin {
+            Ok(search.filtered_pathes())
         } else {
-            print_result(
-                search
-                    .walk_dir(Path::new(args.path), args.key_to_search, &find_key_file)
-                    .expect("failed, Maybe contain invalid utf-8 characters."),
-                args.key_to_search,
-                colored::Color::Red,
+            args.path
+                .iter()
+                .map(|path| search.list_files(Path::new(path)))
+                .collect::<Result<Vec<_>, _>>()
+                .map(|files| files.into_iter().flatten().collect::<Vec<_>>())
+        }
+        .unwrap_or_else(|err| {
+            eprintln!("{}", err.to_string().red());
+            std::process::exit(1);
+        });
+        for file in &files {
+            println!("{file}");
+        }
+        println!("{} files", with_commas(files.len()).green());
+        return;
+    }
+
+    let mut found = if args.staged {
+        search.search_staged(args.key_to_search)
+    } else if args.stdin {
+        search.search_pathes(args.key_to_search)
+    } else if let Some(index) = args.use_index {
+        let index = SymbolIndex::load(index).unwrap_or_else(|err| {
+            eprintln!("{}", format!("Can't load the index {index}: {err}").red());
+            std::process::exit(1);
+        });
+        let stale = index.stale_files().len();
+        if stale > 0 && !args.quiet {
+            eprintln!(
+                "{}",
+                format!(
+                    "{} files changed since indexing are searched again, \
+                     use --index to update the index",
+                    with_commas(stale)
+                )
+                .yellow()
             );
         }
-    } else if args.is_symbol {
+        search.search_index(&index, args.key_to_search)
+    } else if let Some(rev) = args.diff {
+        let paths: Vec<&Path> = args.path.iter().map(Path::new).collect();
+        search.search_diff(&paths, args.key_to_search, rev)
+    } else if let Some(rev) = args.rev {
+        let paths: Vec<&Path> = args.path.iter().map(Path::new).collect();
+        search.search_rev(&paths, args.key_to_search, rev)
+    } else {
+        let paths: Vec<&Path> = args.path.iter().map(Path::new).collect();
+        search.search_all(&paths, args.key_to_search)
+    }
+    .unwrap_or_else(|err| {
+        eprintln!("{}", err.to_string().red());
+        std::process::exit(1);
+    });
+
+    if args.quiet {
+        std::process::exit(if found.is_empty() { 1 } else { 0 });
+    }
+    let capped = cap_result(&mut found, result_cap)
+        || result_cap.is_some_and(|cap| streamed.load(Ordering::Relaxed) > cap);
+
+    let summary = Summary {
+        scanned: search.scanned(),
+        matched_files: found.len(),
+        matches: found.values().map(Vec::len).sum(),
+        elapsed: start.elapsed(),
+    };
+    if let Some(replacement) = args.replace.filter(|_| args.write) {
+        let replaced =
+            write_replaced(&found, &matcher, replacement, args.regex).unwrap_or_else(|err| {
+                eprintln!("{}", err.to_string().red());
+                std::process::exit(1);
+            });
+        println!(
+            "{} replacements written to {} files",
+            with_commas(replaced).green(),
+            with_commas(found.len()).green()
+        );
+    }
+    if let Some(index_file) = args.index {
+        let index = SymbolIndex::from_found(&found);
+        if let Err(err) = index.save(index_file) {
+            eprintln!(
+                "{}",
+                format!("Can't write the index to {index_file}: {err}").red()
+            );
+            std::process::exit(1);
+        }
+        println!(
+            "{} symbols of {} files indexed to {}",
+            with_commas(index.symbols()).green(),
+            with_commas(index.files()).green(),
+            index_file.purple()
+        );
+        return;
+    }
+    if args.tags {
+        let output = args.output.as_deref().unwrap_or("tags");
+        if let Err(err) = std::fs::write(output, to_tags(&found)) {
+            eprintln!(
+                "{}",
+                format!("Can't write the tags to {output}: {err}").red()
+            );
+            std::process::exit(1);
+        }
+        println!(
+            "{} tags written to {}",
+            with_commas(summary.matches).green(),
+            output.purple()
+        );
+        return;
+    }
+    if args.interactive {
+        if let Err(err) = tui::browse(&found) {
+            eprintln!("{}", format!("Can't browse the result: {err}").red());
+            std::process::exit(1);
+        }
+        return;
+    }
+    // the statistics are the only stdout with `--stats-json`, the result only goes to the file.
+    if !args.stats_json || args.output.is_some() {
         print_result(
-            get_symbols(args.path, args.key_to_search, &mut search.parser)
-                .expect("failed, Maybe contain invalid utf-8 characters."),
-            args.key_to_search,
+            restyle_paths(found, args.path_style),
+            &args,
+            &matcher,
             colored::Color::Red,
         );
-    } else {
+    }
+    if !args.no_summary
+        && !args.stats_json
+        && !args.files_with_matches
+        && !args.count
+        && !args.count_matches
+        && args.format == OutputFormat::Text
+    {
+        println!("{summary}");
+    }
+    if capped {
+        warn_capped(&args);
+    }
+    if args.stats && !args.stats_json && args.format == OutputFormat::Text {
+        print_stats(search.stats());
+    }
+    if args.stats_json {
+        println!("{}", stats_json(&summary, search.stats()));
+    }
+    if args.watch {
+        watch_result(&mut search, &args, &matcher);
+    }
+}
+
+/// Search again and print the result each time the searched files change.
+fn watch_result(search: &mut SearchCode, args: &CommandArgs, matcher: &KeyMatcher) {
+    let paths: Vec<&Path> = args.path.iter().map(Path::new).collect();
+    let watcher = Watcher::new(&paths).unwrap_or_else(|err| {
+        eprintln!("{}", format!("Can't watch the files: {err}").red());
+        std::process::exit(1);
+    });
+    loop {
+        let changed = watcher.wait();
+        if changed.is_empty() {
+            return;
+        }
+        if !changed.iter().any(|path| search.is_watched(path)) {
+            continue;
+        }
+        let start = Instant::now();
+        let scanned = search.scanned();
+        let mut found = match search.search_all(&paths, args.key_to_search) {
+            Ok(found) => found,
+            Err(err) => {
+                eprintln!("{}", err.to_string().red());
+                continue;
+            }
+        };
+        let capped = cap_result(&mut found, result_cap(args));
+        let summary = Summary {
+            scanned: search.scanned() - scanned,
+            matched_files: found.len(),
+            matches: found.values().map(Vec::len).sum(),
+            elapsed: start.elapsed(),
+        };
+        // clear the screen and move the cursor to the top left.
+        print!("\x1B[2J\x1B[H");
         print_result(
-            find_key_file(args.path, args.key_to_search, &mut search.parser)
-                .expect("failed, Maybe contain invalid utf-8 characters."),
-            args.key_to_search,
+            restyle_paths(found, args.path_style),
+            args,
+            matcher,
             colored::Color::Red,
         );
+        if !args.no_summary
+            && !args.files_with_matches
+            && !args.count
+            && !args.count_matches
+            && args.format == OutputFormat::Text
+        {
+            println!("{summary}");
+        }
+        if capped {
+            warn_capped(args);
+        }
+    }
+}
+
+/// The max lines to print before asking to refine the query.
+///
+/// *NOTE:* the tags, the index and the written replacements are never capped,
+/// they need all the lines.
+fn result_cap(args: &CommandArgs) -> Option<usize> {
+    (args.result_cap > 0 && !args.tags && args.index.is_none() && !args.write)
+        .then_some(args.result_cap)
+}
+
+/// Drop the found lines over the result cap.
+///
+/// *Return:* `true` if any line is dropped.
+fn cap_result(found: &mut FindLines, result_cap: Option<usize>) -> bool {
+    let Some(cap) = result_cap else {
+        return false;
+    };
+    let capped = found.values().map(Vec::len).sum::<usize>() > cap;
+    if capped {
+        truncate_total(found, cap);
     }
+    capped
 }
 
-fn print_result(result: FindLines, key: &str, color_paint: colored::Color) {
-    for (filename, find) in result {
-        println!("{}", filename.purple());
-        for (line_number, line) in find {
-            let new_line = line.replace(key, key.color(color_paint).to_string().as_str());
-            println!("{}:{}", line_number.to_string().green(), new_line);
+/// Show the filenames of the result in the path style, see `styled_path`.
+fn restyle_paths(found: FindLines, path_style: Option<PathStyle>) -> FindLines {
+    if path_style.is_none() {
+        return found;
+    }
+    let cwd = current_dir();
+    found
+        .into_iter()
+        .map(|(filename, find)| (styled_path(&filename, path_style, cwd.as_deref()), find))
+        .collect()
+}
+
+/// The canonical current directory, to show the paths relative to it.
+fn current_dir() -> Option<PathBuf> {
+    std::env::current_dir()
+        .and_then(|cwd| cwd.canonicalize())
+        .ok()
+}
+
+/// Show the filename relative to `cwd` or absolute, or as scanned if `path_style` is `None`.
+///
+/// *NOTE:* the name not on the disk is kept, like the archive entry or the file at a revision.
+/// The relative path is still absolute if outside `cwd`, so it can always be opened.
+fn styled_path(filename: &str, path_style: Option<PathStyle>, cwd: Option<&Path>) -> String {
+    let Some(path_style) = path_style else {
+        return filename.to_string();
+    };
+    let Ok(absolute) = std::fs::canonicalize(filename) else {
+        return filename.to_string();
+    };
+    let path = match path_style {
+        PathStyle::Absolute => absolute.as_path(),
+        PathStyle::Relative => cwd
+            .and_then(|cwd| absolute.strip_prefix(cwd).ok())
+            .unwrap_or(&absolute),
+    };
+    path.display().to_string()
+}
+
+/// Tell the search is stopped by the result cap.
+fn warn_capped(args: &CommandArgs) {
+    eprintln!(
+        "{}",
+        format!(
+            "Stopped after {} matches; refine your query or raise --result-cap",
+            with_commas(args.result_cap)
+        )
+        .yellow()
+    );
+}
+
+/// Tell the symbol kinds unknown for the languages, they are ignored.
+///
+/// *NOTE:* with `-l any` or `--auto`, only the kinds unknown for all the languages are told.
+fn warn_unknown_kinds(args: &CommandArgs) {
+    let langs: Vec<&Lang> = args
+        .langs
+        .iter()
+        .filter(|lang| **lang != Lang::Any)
+        .collect();
+    if args.auto || langs.is_empty() {
+        let unknown: Vec<_> = args
+            .kinds
+            .iter()
+            .filter(|kind| {
+                !Lang::SUPPORTED
+                    .iter()
+                    .any(|lang| get_query_kinds(lang).contains(kind))
+            })
+            .copied()
+            .collect();
+        if !unknown.is_empty() {
+            eprintln!(
+                "{}",
+                format!("Unknown symbol kinds ignored: {}", unknown.join(",")).yellow()
+            );
         }
-        println!();
+        return;
     }
+    for lang in langs {
+        let known = get_query_kinds(lang);
+        let unknown: Vec<_> = args
+            .kinds
+            .iter()
+            .filter(|kind| !known.contains(kind))
+            .copied()
+            .collect();
+        if !unknown.is_empty() {
+            eprintln!(
+                "{}",
+                format!(
+                    "Unknown symbol kinds for {} ignored: {}, the kinds are {}",
+                    format!("{lang:?}").to_lowercase(),
+                    unknown.join(","),
+                    known.join(",")
+                )
+                .yellow()
+            );
+        }
+    }
+}
+
+/// Print the files and the lines found per language, the most files first.
+fn print_stats(stats: &HashMap<Lang, LangStats>) {
+    let mut stats: Vec<_> = stats.iter().collect();
+    stats.sort_by(|(_, a), (_, b)| b.files.cmp(&a.files).then(b.matches.cmp(&a.matches)));
+    let mut table = Table::new();
+    table.set_titles(row!["lang", "files", "matches"]);
+    for (lang, stats) in stats {
+        table.add_row(row![
+            format!("{lang:?}").to_lowercase(),
+            r->with_commas(stats.files),
+            r->with_commas(stats.matches)
+        ]);
+    }
+    table.printstd();
 }
 
-struct SearchCode {
-    parser: SpecifyParser,
-    lang: Lang,
-    pathes: Vec<String>,
-    ignore_pathes: IgnoreDir,
+/// Serialize the summary and the stats per language to a JSON object, for `--stats-json`.
+fn stats_json(summary: &Summary, stats: &HashMap<Lang, LangStats>) -> String {
+    let langs: BTreeMap<String, _> = stats
+        .iter()
+        .map(|(lang, stats)| {
+            (
+                format!("{lang:?}").to_lowercase(),
+                serde_json::json!({ "files": stats.files, "matches": stats.matches }),
+            )
+        })
+        .collect();
+    serde_json::json!({
+        "scanned_files": summary.scanned,
+        "matched_files": summary.matched_files,
+        "matches": summary.matches,
+        "langs": langs,
+        "elapsed_ms": summary.elapsed.as_millis() as u64,
+    })
+    .to_string()
 }
 
-/// filename -> [(line_number, line)...]
-type FindLines = HashMap<String, Vec<(usize, String)>>;
+/// The summary of the search, printed after the result.
+struct Summary {
+    scanned: usize,
+    matched_files: usize,
+    matches: usize,
+    elapsed: Duration,
+}
 
-impl SearchCode {
-    fn build(lang: &Lang, ignore: IgnoreDir) -> Self {
-        Self {
-            parser: SpecifyParser::from_lang(lang),
-            lang: lang.clone(),
-            pathes: vec![],
-            ignore_pathes: ignore,
+impl std::fmt::Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Scanned {} files, {} matches in {} files in {:.1}s",
+            with_commas(self.scanned).green(),
+            with_commas(self.matches).green(),
+            with_commas(self.matched_files).green(),
+            self.elapsed.as_secs_f64()
+        )
+    }
+}
+
+/// Format the number with `,` as the thousands separator, like `1,240`.
+fn with_commas(number: usize) -> String {
+    let digits = number.to_string();
+    let mut formatted = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            formatted.push(',');
         }
+        formatted.push(digit);
     }
+    formatted
+}
 
-    /// Walk through the directory and do the operation.
-    ///
-    /// *Return:* : The vector of the found that contains key.
-    /// - The first element is the filename.
-    /// - The second element is the line number.
-    /// - The third element is the line contains key.
-    fn walk_dir<Operating>(
-        &mut self,
-        dirname: &Path,
-        key: &str,
-        op: &Operating,
-    ) -> Result<FindLines, io::Error>
-    where
-        Operating: Fn(&str, &str, &mut SpecifyParser) -> Result<FindLines, io::Error>,
-    {
-        let mut found = HashMap::new();
-        self.scan_dir(dirname);
-        let progress_bar = ProgressBar::new(self.pathes.len() as u64);
-        progress_bar.set_style(
-            ProgressStyle::with_template("[{elapsed}] {bar:40.cyan/blue} [{pos:>5}/{len:5}] {msg}")
-                .unwrap()
-                .progress_chars("##-"),
+/// The counts of the found lines of one file for `--count` and `--count-matches`.
+///
+/// *NOTE:* a symbol counts as one match, and a line matched only by an empty key
+/// counts as one match too.
+///
+/// *Return:* `lines`, `matches` or `lines:matches` when both are asked.
+fn counts(find: &[FoundLine], args: &CommandArgs, matcher: &KeyMatcher) -> String {
+    let lines = find
+        .iter()
+        .map(|found| found.line)
+        .collect::<HashSet<_>>()
+        .len();
+    let matches: usize = find
+        .iter()
+        .map(|found| match found.symbol {
+            Some(_) => 1,
+            None => search_one_line(&found.text, matcher).len().max(1),
+        })
+        .sum();
+    match (args.count, args.count_matches) {
+        (true, true) => format!("{lines}:{matches}"),
+        (false, true) => matches.to_string(),
+        _ => lines.to_string(),
+    }
+}
+
+/// Build the glob set, or exit if any glob is invalid.
+fn globset_or_exit(patterns: &[String]) -> Option<GlobSet> {
+    build_globset(patterns).unwrap_or_else(|err| {
+        eprintln!("{}", format!("Invalid glob: {err}").red());
+        std::process::exit(1);
+    })
+}
+
+/// Print the result to the terminal, or write it to `args.output` if given.
+///
+/// *NOTE:* the file gets the uncolored result, the terminal just a colored summary.
+/// The files are sorted by name and the lines of each file by line number.
+/// With `args.unique`, the identical lines of each file are printed once in the text format.
+fn print_result(
+    mut result: FindLines,
+    args: &CommandArgs,
+    matcher: &KeyMatcher,
+    color_paint: colored::Color,
+) {
+    // filename -> {line number -> count} of the lines printed once for the identical ones.
+    let mut repeated: Repeated = HashMap::new();
+    for (filename, find) in result.iter_mut() {
+        find.sort_by_key(|found| found.line);
+        if args.unique && args.format == OutputFormat::Text {
+            repeated.insert(filename.clone(), unique_lines(find));
+        }
+    }
+    let context = if args.is_symbol {
+        args.symbol_context
+    } else {
+        0
+    };
+
+    if let Some(output) = args.output.as_deref() {
+        if let Err(err) = write_result(&result, output, args, matcher, &repeated) {
+            eprintln!(
+                "{}",
+                format!("Can't write the result to {output}: {err}").red()
+            );
+            std::process::exit(1);
+        }
+        if args.stats_json {
+            return;
+        }
+        let lines: usize = result.values().map(Vec::len).sum();
+        println!(
+            "{} lines in {} files written to {}",
+            lines.to_string().green(),
+            result.len().to_string().green(),
+            output.purple()
         );
-        progress_bar.set_message(format!("Searching \"{key}\" ...").bright_blue().to_string());
-        progress_bar.enable_steady_tick(Duration::from_millis(100));
+        return;
+    }
 
-        for path in &self.pathes {
-            progress_bar.inc(1);
-            let extension = path.split('.').last().unwrap();
-            if self.lang != Lang::Any && !self.lang.valid_extensions().contains(&extension) {
-                continue;
+    if args.format == OutputFormat::JsonLines {
+        print!("{}", to_json_lines(&result, args));
+        return;
+    }
+    if args.format == OutputFormat::Vimgrep {
+        print!("{}", to_vimgrep(&result, args, matcher));
+        return;
+    }
+    if args.format != OutputFormat::Text {
+        let structured = match args.format {
+            OutputFormat::Sarif => to_sarif(&result, args),
+            OutputFormat::Html => to_html(&result, args, matcher),
+            _ => to_json(&result, args),
+        };
+        println!("{structured}");
+        return;
+    }
+
+    if args.files_with_matches {
+        for (filename, _) in sorted_files(&result, args.sort) {
+            if args.null {
+                print!("{filename}\0");
+            } else {
+                println!("{}", filename.purple());
             }
-            let result = op(path, key, &mut self.parser)?;
-            found.extend(result);
-        }
-        progress_bar.finish();
-
-        Ok(found)
-    }
-
-    /// Scan the directory and return the vector of the files' path.
-    ///
-    /// *NOTE:* will change self.pathes
-    fn scan_dir(&mut self, dirname: &Path) {
-        if !self.ignore_pathes.is_ignore(dirname.to_str().unwrap()) {
-            if dirname.is_dir() {
-                for entry in std::fs::read_dir(dirname)
-                    .unwrap_or_else(|_| panic!("Can't read the directory: {:?}", dirname))
-                {
-                    let entry = entry.expect("Can't read the entry");
-                    let path = entry.path();
-                    if self.ignore_pathes.is_ignore(path.to_str().unwrap()) {
-                        continue;
-                    }
-                    if path.is_dir() {
-                        self.scan_dir(&path);
+        }
+        return;
+    }
+
+    if args.count || args.count_matches {
+        for (filename, find) in sorted_files(&result, args.sort) {
+            println!("{}:{}", filename.purple(), counts(find, args, matcher));
+        }
+        return;
+    }
+
+    if args.is_symbol && args.symbol_match == SymbolMatch::Fuzzy {
+        let ranked = ranked(&result);
+        let width = number_width(ranked.iter().map(|(_, found)| found.line), args);
+        for (filename, found) in ranked {
+            println!(
+                "{} {}:{}:{}",
+                found.score.unwrap_or_default().to_string().yellow(),
+                filename.purple(),
+                padded_line(found.line, width, args).green(),
+                highlight_found(&signature_found(found), matcher, color_paint)
+            );
+        }
+        return;
+    }
+
+    for (filename, find) in sorted_files(&result, args.sort) {
+        // the filename prefixes each line without the heading.
+        let prefix = if args.no_heading {
+            format!("{}:", filename.purple())
+        } else {
+            println!("{}", filename.purple());
+            String::new()
+        };
+        let width = number_width(find.iter().map(|found| found.line), args);
+        if args.only_matching {
+            for found in find {
+                for matched in matched_texts(found, matcher) {
+                    println!(
+                        "{prefix}{}:{}",
+                        padded_line(found.line, width, args).green(),
+                        matched.color(color_paint)
+                    );
+                }
+            }
+        } else if context > 0 || args.context_function_body {
+            // the virtual names, like `HEAD:a.rs` or `x.zip:entry`, can't be read again.
+            let blocks = match read_context(
+                filename,
+                find,
+                context,
+                args.context_function_body,
+                args.encoding,
+            ) {
+                Ok(blocks) => blocks,
+                Err(err) => {
+                    eprintln!(
+                        "{}",
+                        format!("Skipped {filename}: can't read the context: {err}").yellow()
+                    );
+                    continue;
+                }
+            };
+            let width = number_width(
+                blocks
+                    .iter()
+                    .flat_map(|(_, lines)| lines.last().map(|(line, _)| *line)),
+                args,
+            );
+            for (index, ((anchor, lines), found)) in blocks.into_iter().zip(find).enumerate() {
+                if index > 0 {
+                    println!("{}", "--".dimmed());
+                }
+                for (line_number, line) in lines {
+                    if line_number == anchor {
+                        let (found, clipped) = clip_found(found, args.max_columns);
+                        let new_line = highlight_found(&found, matcher, color_paint);
+                        println!(
+                            "{prefix}{}:{}{}",
+                            padded_line(line_number, width, args).green(),
+                            new_line,
+                            clipped.dimmed()
+                        );
                     } else {
-                        self.pathes.push(path.to_str().unwrap().to_string());
+                        let (line, clipped) = clip_line(&line, args.max_columns);
+                        println!(
+                            "{prefix}{}-{}{}",
+                            padded_line(line_number, width, args).dimmed(),
+                            line,
+                            clipped.dimmed()
+                        );
                     }
                 }
-            } else {
-                self.pathes.push(dirname.to_str().unwrap().to_string());
             }
+        } else if args.group_by_symbol && !args.is_symbol {
+            for (scope, find) in group_by_scope(find) {
+                println!("{}", scope.unwrap_or(NO_SCOPE).cyan().bold());
+                for found in find {
+                    let (clipped_found, clipped) = clip_found(found, args.max_columns);
+                    let new_line = highlight_found(&clipped_found, matcher, color_paint);
+                    let count = repeated_suffix(&repeated, filename, found.line);
+                    println!(
+                        "{prefix}  {}:{}{}{}",
+                        padded_line(found.line, width, args).green(),
+                        new_line,
+                        clipped.dimmed(),
+                        count.yellow()
+                    );
+                }
+            }
+        } else {
+            for found in find {
+                if let Some(replacement) = args.replace {
+                    let (removed, added) =
+                        replace_preview(&found.text, matcher, replacement, args.regex);
+                    println!(
+                        "{prefix}{}-{}",
+                        padded_line(found.line, width, args).green(),
+                        removed
+                    );
+                    println!(
+                        "{prefix}{}+{}",
+                        padded_line(found.line, width, args).green(),
+                        added
+                    );
+                    continue;
+                }
+                let (clipped_found, clipped) =
+                    clip_found(&signature_found(found), args.max_columns);
+                let new_line = highlight_found(&clipped_found, matcher, color_paint);
+                let scope = scope_suffix(found).dimmed();
+                let count = repeated_suffix(&repeated, filename, found.line);
+                println!(
+                    "{prefix}{}:{}{}{}{scope}",
+                    padded_line(found.line, width, args).green(),
+                    new_line,
+                    clipped.dimmed(),
+                    count.yellow()
+                );
+            }
+        }
+        if !args.no_heading {
+            println!();
         }
     }
 }
 
-/// This is used when just search not support language.
+/// Cut the line to the first `max_columns` chars, if set.
 ///
-/// **Supported languages are:**
-/// - Cpp
-/// - Python
-/// - Rust
-/// - C: treat as Cpp
+/// *Return:* (the cut line, the note like ` [+1024 more]`, or empty if not cut).
+fn clip_line(line: &str, max_columns: Option<usize>) -> (&str, String) {
+    match max_columns.and_then(|max_columns| line.char_indices().nth(max_columns)) {
+        Some((end, _)) => (
+            &line[..end],
+            format!(" [+{} more]", line[end..].chars().count()),
+        ),
+        None => (line, String::new()),
+    }
+}
+
+/// Cut the found line like `clip_line`, the note tells if the match is cut off too.
+fn clip_found(found: &FoundLine, max_columns: Option<usize>) -> (FoundLine, String) {
+    let (text, mut clipped) = clip_line(&found.text, max_columns);
+    // the match starts at the byte `col - 1`.
+    if !clipped.is_empty() && found.col > text.len() {
+        clipped.push_str(" [match elided]");
+    }
+    let found = FoundLine {
+        text: text.to_string(),
+        ..found.clone()
+    };
+    (found, clipped)
+}
+
+/// The symbol of the found line in the symbol search, or each match of the key.
+fn matched_texts<'a>(found: &'a FoundLine, matcher: &KeyMatcher) -> Vec<&'a str> {
+    match &found.symbol {
+        Some(symbol) => vec![symbol.as_str()],
+        None => search_one_line(&found.text, matcher)
+            .into_iter()
+            .map(|range| &found.text[range])
+            .collect(),
+    }
+}
+
+/// The found line with the signature of the symbol as the text, see `FoundLine::signature`.
 ///
-fn find_key_file(
-    filename: &str,
-    key: &str,
-    _placeholder: &mut SpecifyParser,
-) -> Result<FindLines, io::Error> {
-    let file = File::open(filename)?;
+/// *NOTE:* the column is moved to the symbol in the signature,
+/// the line is kept if the symbol is not in it.
+fn signature_found(found: &FoundLine) -> Cow<'_, FoundLine> {
+    let (Some(signature), Some(symbol)) = (&found.signature, &found.symbol) else {
+        return Cow::Borrowed(found);
+    };
+    match signature.find(symbol.as_str()) {
+        Some(start) => Cow::Owned(FoundLine {
+            text: signature.clone(),
+            col: start + 1,
+            ..found.clone()
+        }),
+        None => Cow::Borrowed(found),
+    }
+}
+
+/// Color the symbol span of the found line in the symbol search, or each match of the key.
+///
+/// *NOTE:* the symbol span starts at the byte column `found.col` and is underlined too.
+fn highlight_found(found: &FoundLine, matcher: &KeyMatcher, color_paint: colored::Color) -> String {
+    let Some(symbol) = &found.symbol else {
+        return highlight_with(&found.text, matcher, color_paint);
+    };
+    let start = found.col - 1;
+    let end = start + symbol.len();
+    match (found.text.get(..start), found.text.get(start..end)) {
+        (Some(before), Some(span)) => format!(
+            "{before}{}{}",
+            span.color(color_paint).underline(),
+            &found.text[end..]
+        ),
+        _ => found.text.clone(),
+    }
+}
+
+/// Show the line before and after replacing each match of the key.
+///
+/// *NOTE:* the `$` in the replacement is expanded if `expand`, see `KeyMatcher::replacements`.
+///
+/// *Return:* (the line with the key in red strikethrough, the line with the replacement in green).
+fn replace_preview(
+    line: &str,
+    matcher: &KeyMatcher,
+    replacement: &str,
+    expand: bool,
+) -> (String, String) {
+    let mut removed = String::with_capacity(line.len());
+    let mut added = String::with_capacity(line.len());
+    let mut last = 0;
+    for (range, replacement) in matcher.replacements(line, replacement, expand) {
+        removed.push_str(&line[last..range.start]);
+        removed.push_str(&line[range.clone()].red().strikethrough().to_string());
+        added.push_str(&line[last..range.start]);
+        added.push_str(&replacement.as_ref().green().to_string());
+        last = range.end;
+    }
+    removed.push_str(&line[last..]);
+    added.push_str(&line[last..]);
+    (removed, added)
+}
+
+/// The group of the matches outside any function or class, with `--group-by-symbol`.
+const NO_SCOPE: &str = "(top level)";
+
+/// Group the found lines by their enclosing scopes, in the order the scopes first appear.
+///
+/// *NOTE:* the lines without scope, outside any symbol or not parsed, are grouped together.
+fn group_by_scope(find: &[FoundLine]) -> Vec<(Option<&str>, Vec<&FoundLine>)> {
+    let mut groups: Vec<(Option<&str>, Vec<&FoundLine>)> = Vec::new();
+    for found in find {
+        let scope = found.scope.as_deref();
+        match groups.iter_mut().find(|(group, _)| *group == scope) {
+            Some((_, lines)) => lines.push(found),
+            None => groups.push((scope, vec![found])),
+        }
+    }
+    groups
+}
+
+/// The suffix like `  in function main` of the enclosing scope,
+/// or `  at server.ports[0]` of the JSON key path, or `  at resource "aws_instance" "web"` of the HCL block,
+/// or `  (h2)` of the markdown heading, or empty.
+///
+/// *NOTE:* the next symbols are appended like `  next: parse, emit`, if any.
+fn scope_suffix(found: &FoundLine) -> String {
+    let mut suffix = match (&found.scope, &found.key_path, &found.kind) {
+        (Some(scope), _, _) => format!("  in {scope}"),
+        (None, Some(key_path), _) => format!("  at {key_path}"),
+        (None, None, Some(kind)) if is_heading(kind) => format!("  ({kind})"),
+        _ => String::new(),
+    };
+    if let Some(next_symbols) = found.next_symbols.as_ref().filter(|v| !v.is_empty()) {
+        suffix.push_str(&format!("  next: {}", next_symbols.join(", ")));
+    }
+    suffix
+}
+
+/// Check if the symbol kind is the level of a markdown heading, like `h2`.
+fn is_heading(kind: &str) -> bool {
+    matches!(kind, "h1" | "h2" | "h3" | "h4" | "h5" | "h6")
+}
 
-    let reader = BufReader::new(file);
+/// filename -> {line number -> count}
+type Repeated = HashMap<String, HashMap<usize, usize>>;
 
-    let mut found = HashMap::new();
-    for (line_number, line_str) in reader.lines().enumerate() {
-        let line = line_str?;
-        if line.contains(key) {
-            found
-                .entry(filename.to_owned())
-                .or_insert_with(Vec::new)
-                .push((line_number + 1, line));
+/// Keep the first of the identical lines.
+///
+/// *Return:* line number -> count, of the kept lines that have more than one.
+fn unique_lines(find: &mut Vec<FoundLine>) -> HashMap<usize, usize> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for found in find.iter() {
+        *counts.entry(found.text.clone()).or_default() += 1;
+    }
+    let mut repeated = HashMap::new();
+    find.retain(|found| match counts.remove(&found.text) {
+        Some(count) => {
+            if count > 1 {
+                repeated.insert(found.line, count);
+            }
+            true
         }
+        None => false,
+    });
+    repeated
+}
+
+/// The suffix like ` (x5)` of the line printed once for the identical ones, or empty.
+fn repeated_suffix(repeated: &Repeated, filename: &str, line_number: usize) -> String {
+    repeated
+        .get(filename)
+        .and_then(|counts| counts.get(&line_number))
+        .map(|count| format!(" (x{count})"))
+        .unwrap_or_default()
+}
+
+/// The files of the result in the order of `sort`.
+///
+/// *NOTE:* the files can't be stated are the last in the mtime order.
+/// The ties keep the path order.
+fn sorted_files(result: &FindLines, sort: SortBy) -> Vec<(&String, &Vec<FoundLine>)> {
+    let mut files: Vec<_> = result.iter().collect();
+    match sort {
+        SortBy::Path => {}
+        SortBy::Mtime => files.sort_by_cached_key(|(filename, _)| {
+            std::cmp::Reverse(
+                std::fs::metadata(filename)
+                    .and_then(|metadata| metadata.modified())
+                    .ok(),
+            )
+        }),
+        SortBy::Matches => files.sort_by_key(|(_, find)| std::cmp::Reverse(find.len())),
     }
+    files
+}
 
-    Ok(found)
+/// The line number to show, from the 1-based one to `args.line_number_base`.
+fn shown_line(line_number: usize, args: &CommandArgs) -> usize {
+    line_number + args.line_number_base - 1
 }
 
-/** Get the symbols from the code.
+/// The width to right-align the shown line numbers to, the widest of the lines.
+///
+/// *NOTE:* `--line-number-width` overrides it, `0` for no padding.
+fn number_width(lines: impl Iterator<Item = usize>, args: &CommandArgs) -> usize {
+    args.line_number_width.unwrap_or_else(|| {
+        lines
+            .map(|line| shown_line(line, args).to_string().len())
+            .max()
+            .unwrap_or(0)
+    })
+}
+
+/// The shown line number right-aligned to the width.
+fn padded_line(line_number: usize, width: usize, args: &CommandArgs) -> String {
+    format!("{:>width$}", shown_line(line_number, args))
+}
 
-    *NOTE:* This function calls only if lang is not `Any`.
-    or panic;
+/// All the found lines of the fuzzy symbol match, the highest score first.
+fn ranked(result: &FindLines) -> Vec<(&String, &FoundLine)> {
+    let mut ranked: Vec<_> = result
+        .iter()
+        .flat_map(|(filename, find)| find.iter().map(move |found| (filename, found)))
+        .collect();
+    ranked.sort_by_key(|(_, found)| std::cmp::Reverse(found.score));
+    ranked
+}
+
+/// (found_line_number, [(line_number, line)...])
+type ContextBlock = (usize, Vec<(usize, String)>);
 
-    *Return:* The vector of the symbols.
-    - The first element is the filename.
-    - The second element is the line number.
-    - The third element is the line contains symbol.
-*/
-pub fn get_symbols(
+/// Read `context` lines before and after each found line from the file,
+/// or the lines of the function enclosing it with `function_body`, see `FoundLine::body_lines`.
+///
+/// *NOTE:* the file is decoded like in the search, from `--encoding` or lossy UTF-8.
+fn read_context(
     filename: &str,
-    key: &str,
-    parser: &mut SpecifyParser,
-) -> Result<FindLines, io::Error> {
-    let code = std::fs::read_to_string(filename).unwrap();
-    let tree_sitter_lang = parser
-        .get_lang()
-        .into_treesitter_language()
-        .expect(
-"`Can't convert the language to the tree-sitter language. Maybe the language is not supported.
-If you want to search with symbol, please use -l to specify the language.`"
-        );
-    let ast = parser.get_ast(&code);
-
-    let mut query_cursor = QueryCursor::new();
-    let mut symbols_map = HashMap::new();
-
-    for search_query in get_query(parser.get_lang()) {
-        let query =
-            Query::new(&tree_sitter_lang, search_query.replace(":?", key).as_str()).unwrap();
-        let captures = query_cursor.captures(&query, ast.root_node(), code.as_bytes());
-        for (cs, cs_index) in captures {
-            let capture = cs.captures[cs_index];
-            let node = capture.node;
-            let text = node.utf8_text(code.as_bytes()).expect(
-                "`Can't get the text from the node. Maybe containes invalid utf-8 characters.`",
-            );
-            symbols_map
-                .entry(filename.to_owned())
-                .or_insert_with(Vec::new)
-                .push((node.start_position().row + 1, text.to_string()));
+    find: &[FoundLine],
+    context: usize,
+    function_body: bool,
+    encoding: Option<&'static Encoding>,
+) -> Result<Vec<ContextBlock>, io::Error> {
+    let code = decode(std::fs::read(filename)?, encoding);
+    let source: Vec<&str> = code.lines().collect();
+    Ok(find
+        .iter()
+        .map(|found| {
+            let anchor = found.line;
+            let (start, end) = match found.body_lines.filter(|_| function_body) {
+                Some((start, end)) => (start, end.min(source.len())),
+                None => (
+                    anchor.saturating_sub(context).max(1),
+                    (anchor + context).min(source.len()),
+                ),
+            };
+            let lines = (start..=end)
+                .map(|line_number| (line_number, source[line_number - 1].to_string()))
+                .collect();
+            (anchor, lines)
+        })
+        .collect())
+}
+
+/// Stream the JSON lines as each file is searched, see `SearchCode::set_visitor`.
+///
+/// *NOTE:* the result is still kept for the output file, the tags, the index, the interactive,
+/// the written replacements, the quiet and the watch modes, and nothing is printed with `--stats-json`.
+fn streams_json_lines(args: &CommandArgs) -> bool {
+    args.format == OutputFormat::JsonLines
+        && args.output.is_none()
+        && !args.tags
+        && args.index.is_none()
+        && !args.interactive
+        && !args.write
+        && !args.quiet
+        && !args.watch
+        && !args.stats_json
+        // the files are streamed in the path order, the other orders need all the files.
+        && args.sort == SortBy::Path
+}
+
+/// Serialize the result to the newline-delimited JSON, see `json_lines`.
+///
+/// *NOTE:* the files are in the `args.sort` order.
+fn to_json_lines(result: &FindLines, args: &CommandArgs) -> String {
+    sorted_files(result, args.sort)
+        .into_iter()
+        .map(|(filename, find)| {
+            json_lines(
+                filename,
+                find,
+                args.line_number_base,
+                args.files_with_matches,
+            )
+        })
+        .collect()
+}
+
+/// One `{file, line, text}` JSON object in each line for each found line, or one `{file}`
+/// for the file in the files-with-matches mode.
+///
+/// *NOTE:* the line numbers start from `line_number_base`.
+fn json_lines(
+    filename: &str,
+    find: &[FoundLine],
+    line_number_base: usize,
+    files_only: bool,
+) -> String {
+    if files_only {
+        return format!("{}\n", serde_json::json!({ "file": filename }));
+    }
+    find.iter()
+        .map(|found| {
+            let object = serde_json::json!({
+                "file": filename,
+                "line": found.line + line_number_base - 1,
+                "text": found.text,
+            });
+            format!("{object}\n")
+        })
+        .collect()
+}
+
+/// Format the result as `file:line:col:text` lines for the editors, like vim's `grepprg`.
+///
+/// *NOTE:* one line for each match of the key in a found line, or for each symbol.
+/// The column is the 1-based byte column, and the line numbers start from `args.line_number_base`.
+/// The files are in the `args.sort` order.
+fn to_vimgrep(result: &FindLines, args: &CommandArgs, matcher: &KeyMatcher) -> String {
+    let mut lines = String::new();
+    for (filename, find) in sorted_files(result, args.sort) {
+        for found in find {
+            let cols = match &found.symbol {
+                Some(_) => vec![found.col],
+                None => search_one_line(&found.text, matcher)
+                    .into_iter()
+                    .map(|range| range.start + 1)
+                    .collect(),
+            };
+            // the found column is kept if the line has no match alone.
+            let cols = if cols.is_empty() {
+                vec![found.col]
+            } else {
+                cols
+            };
+            for col in cols {
+                lines.push_str(&format!(
+                    "{filename}:{}:{col}:{}\n",
+                    shown_line(found.line, args),
+                    found.text
+                ));
+            }
+        }
+    }
+    lines
+}
+
+/// Serialize the result to JSON, filename -> [{line, col, byte_offset, byte_end, text, symbol}...].
+///
+/// *NOTE:* just the list of the filenames in the files-with-matches mode.
+/// The line numbers start from `args.line_number_base`, the files are in the `args.sort` order.
+fn to_json(result: &FindLines, args: &CommandArgs) -> String {
+    if args.files_with_matches {
+        let files: Vec<_> = sorted_files(result, args.sort)
+            .into_iter()
+            .map(|(filename, _)| filename)
+            .collect();
+        serde_json::to_string_pretty(&files)
+    } else if args.line_number_base != 1 {
+        let mut result = result.clone();
+        for found in result.values_mut().flatten() {
+            found.line = shown_line(found.line, args);
+        }
+        serde_json::to_string_pretty(&SortedFiles(sorted_files(&result, args.sort)))
+    } else {
+        serde_json::to_string_pretty(&SortedFiles(sorted_files(result, args.sort)))
+    }
+    .expect("Can't serialize the result to JSON")
+}
+
+/// The files of `sorted_files`, serialized as a JSON object keeping their order.
+struct SortedFiles<'a>(Vec<(&'a String, &'a Vec<FoundLine>)>);
+
+impl Serialize for SortedFiles<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().copied())
+    }
+}
+
+/// Serialize the result to a SARIF 2.1.0 log, one result for each found line.
+///
+/// *NOTE:* the uri is the path without the leading `./`, the line is always 1-based
+/// as SARIF requires, and the column is the 1-based byte column.
+/// The results are in the `args.sort` order of the files.
+fn to_sarif(result: &FindLines, args: &CommandArgs) -> String {
+    let results: Vec<_> = sorted_files(result, args.sort)
+        .into_iter()
+        .flat_map(|(filename, find)| find.iter().map(move |found| (filename, found)))
+        .map(|(filename, found)| {
+            let mut region = serde_json::json!({
+                "startLine": found.line,
+                "startColumn": found.col,
+            });
+            if let Some(byte_offset) = found.byte_offset {
+                region["byteOffset"] = byte_offset.into();
+                if let Some(byte_length) = found.byte_end.map(|end| end - byte_offset) {
+                    region["byteLength"] = byte_length.into();
+                    // the match in the line, not across the lines like a multiline symbol.
+                    if found.col - 1 + byte_length <= found.text.len() {
+                        region["endColumn"] = (found.col + byte_length).into();
+                    }
+                }
+            }
+            serde_json::json!({
+                "ruleId": "match",
+                "level": "note",
+                "message": { "text": found.text },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": {
+                            "uri": filename.trim_start_matches("./").replace('\\', "/"),
+                        },
+                        "region": region,
+                    },
+                }],
+            })
+        })
+        .collect();
+    let log = serde_json::json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": [{
+                        "id": "match",
+                        "shortDescription": { "text": format!("Found \"{}\"", args.key_to_search) },
+                    }],
+                },
+            },
+            "results": results,
+        }],
+    });
+    serde_json::to_string_pretty(&log).expect("Can't serialize the result to SARIF")
+}
+
+/// Format the found symbols as a ctags file, one `symbol\tfile\t/^line$/;"\tkind`
+/// line for each symbol, sorted by the symbol like ctags.
+///
+/// *NOTE:* the `\` and the `/` in the line are escaped for the search pattern.
+fn to_tags(result: &FindLines) -> String {
+    let mut tags: Vec<_> = result
+        .iter()
+        .flat_map(|(filename, find)| find.iter().map(move |found| (filename, found)))
+        .filter_map(|(filename, found)| {
+            let symbol = found.symbol.as_deref()?;
+            let pattern = found.text.replace('\\', "\\\\").replace('/', "\\/");
+            let kind = tag_kind(found.kind.as_deref().unwrap_or_default());
+            Some(format!(
+                "{symbol}\t{}\t/^{pattern}$/;\"\t{kind}",
+                filename.trim_start_matches("./")
+            ))
+        })
+        .collect();
+    tags.sort();
+    let mut tags_file = String::from(
+        "!_TAG_FILE_FORMAT\t2\t/extended format/\n!_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted, 2=foldcase/\n",
+    );
+    for tag in tags {
+        tags_file.push_str(&tag);
+        tags_file.push('\n');
+    }
+    tags_file
+}
+
+/// The single char ctags kind of the symbol kind, like `f` for the functions.
+fn tag_kind(kind: &str) -> char {
+    match kind {
+        "function" => 'f',
+        "method" => 'm',
+        "struct" => 's',
+        "class" => 'c',
+        "interface" | "protocol" => 'i',
+        "enum" => 'g',
+        "object" => 'o',
+        "constant" => 'v',
+        "key" => 'k',
+        _ => kind.chars().next().unwrap_or('x'),
+    }
+}
+
+/// Render the result to a self-contained HTML report, a table of the files linked to
+/// the table of the found lines of each file, with the matches in `<mark>`.
+///
+/// *NOTE:* the files are in the `args.sort` order, the symbol span is marked in the
+/// symbol search.
+fn to_html(result: &FindLines, args: &CommandArgs, matcher: &KeyMatcher) -> String {
+    let files = sorted_files(result, args.sort);
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!(
+        "<title>search_code: {}</title>\n",
+        escape_html(args.key_to_search)
+    ));
+    html.push_str(
+        "<style>\n\
+         body { font-family: sans-serif; }\n\
+         table { border-collapse: collapse; margin-bottom: 2em; }\n\
+         td, th { border: 1px solid #ccc; padding: 2px 8px; text-align: left; }\n\
+         td.code { font-family: monospace; white-space: pre; }\n\
+         </style>\n</head>\n<body>\n",
+    );
+    html.push_str(&format!(
+        "<h1>Found \"{}\"</h1>\n",
+        escape_html(args.key_to_search)
+    ));
+
+    html.push_str("<table>\n<tr><th>File</th><th>Matches</th></tr>\n");
+    for (index, (filename, find)) in files.iter().enumerate() {
+        html.push_str(&format!(
+            "<tr><td><a href=\"#file-{index}\">{}</a></td><td>{}</td></tr>\n",
+            escape_html(filename),
+            find.len()
+        ));
+    }
+    html.push_str("</table>\n");
+
+    for (index, (filename, find)) in files.iter().enumerate() {
+        html.push_str(&format!(
+            "<h2 id=\"file-{index}\">{}</h2>\n<table>\n",
+            escape_html(filename)
+        ));
+        for found in find.iter() {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td class=\"code\">{}</td></tr>\n",
+                shown_line(found.line, args),
+                mark_found(found, matcher)
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</body>\n</html>");
+    html
+}
+
+/// Escape the HTML-special characters.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
         }
     }
+    escaped
+}
 
-    Ok(symbols_map)
+/// The escaped found line with the symbol span or each match of the key in `<mark>`.
+fn mark_found(found: &FoundLine, matcher: &KeyMatcher) -> String {
+    let spans: Vec<(usize, usize)> = match &found.symbol {
+        Some(symbol) => vec![(found.col - 1, found.col - 1 + symbol.len())],
+        None => search_one_line(&found.text, matcher)
+            .into_iter()
+            .map(|range| (range.start, range.end))
+            .collect(),
+    };
+    let mut marked = String::with_capacity(found.text.len());
+    let mut last = 0;
+    for (start, end) in spans {
+        let (Some(before), Some(span)) = (found.text.get(last..start), found.text.get(start..end))
+        else {
+            break;
+        };
+        marked.push_str(&escape_html(before));
+        marked.push_str(&format!("<mark>{}</mark>", escape_html(span)));
+        last = end;
+    }
+    marked.push_str(&escape_html(&found.text[last..]));
+    marked
+}
+
+/// Write the uncolored result to the file, the file will be created or truncated.
+fn write_result(
+    result: &FindLines,
+    output: &str,
+    args: &CommandArgs,
+    matcher: &KeyMatcher,
+    repeated: &Repeated,
+) -> Result<(), io::Error> {
+    let mut writer = BufWriter::new(File::create(output)?);
+    let context = if args.is_symbol {
+        args.symbol_context
+    } else {
+        0
+    };
+    if args.format == OutputFormat::JsonLines {
+        write!(writer, "{}", to_json_lines(result, args))?;
+        return writer.flush();
+    }
+    if args.format == OutputFormat::Vimgrep {
+        write!(writer, "{}", to_vimgrep(result, args, matcher))?;
+        return writer.flush();
+    }
+    if args.format != OutputFormat::Text {
+        let structured = match args.format {
+            OutputFormat::Sarif => to_sarif(result, args),
+            OutputFormat::Html => to_html(result, args, matcher),
+            _ => to_json(result, args),
+        };
+        writeln!(writer, "{structured}")?;
+        return writer.flush();
+    }
+    if args.files_with_matches {
+        let end = if args.null { '\0' } else { '\n' };
+        for (filename, _) in sorted_files(result, args.sort) {
+            write!(writer, "{filename}{end}")?;
+        }
+        return writer.flush();
+    }
+    if args.count || args.count_matches {
+        for (filename, find) in sorted_files(result, args.sort) {
+            writeln!(writer, "{filename}:{}", counts(find, args, matcher))?;
+        }
+        return writer.flush();
+    }
+    if args.is_symbol && args.symbol_match == SymbolMatch::Fuzzy {
+        let ranked = ranked(result);
+        let width = number_width(ranked.iter().map(|(_, found)| found.line), args);
+        for (filename, found) in ranked {
+            let score = found.score.unwrap_or_default();
+            writeln!(
+                writer,
+                "{score} {filename}:{}:{}",
+                padded_line(found.line, width, args),
+                found.text
+            )?;
+        }
+        return writer.flush();
+    }
+
+    for (filename, find) in sorted_files(result, args.sort) {
+        let prefix = if args.no_heading {
+            format!("{filename}:")
+        } else {
+            writeln!(writer, "{filename}")?;
+            String::new()
+        };
+        let width = number_width(find.iter().map(|found| found.line), args);
+        if args.only_matching {
+            for found in find {
+                for matched in matched_texts(found, matcher) {
+                    writeln!(
+                        writer,
+                        "{prefix}{}:{matched}",
+                        padded_line(found.line, width, args)
+                    )?;
+                }
+            }
+        } else if context > 0 || args.context_function_body {
+            let blocks = read_context(
+                filename,
+                find,
+                context,
+                args.context_function_body,
+                args.encoding,
+            )?;
+            let width = number_width(
+                blocks
+                    .iter()
+                    .flat_map(|(_, lines)| lines.last().map(|(line, _)| *line)),
+                args,
+            );
+            for (index, (anchor, lines)) in blocks.into_iter().enumerate() {
+                if index > 0 {
+                    writeln!(writer, "--")?;
+                }
+                for (line_number, line) in lines {
+                    let separator = if line_number == anchor { ':' } else { '-' };
+                    let (line, clipped) = clip_line(&line, args.max_columns);
+                    writeln!(
+                        writer,
+                        "{prefix}{}{separator}{line}{clipped}",
+                        padded_line(line_number, width, args)
+                    )?;
+                }
+            }
+        } else if args.group_by_symbol && !args.is_symbol {
+            for (scope, find) in group_by_scope(find) {
+                writeln!(writer, "{}", scope.unwrap_or(NO_SCOPE))?;
+                for found in find {
+                    let (clipped_found, clipped) = clip_found(found, args.max_columns);
+                    let count = repeated_suffix(repeated, filename, found.line);
+                    writeln!(
+                        writer,
+                        "{prefix}  {}:{}{clipped}{count}",
+                        padded_line(found.line, width, args),
+                        clipped_found.text
+                    )?;
+                }
+            }
+        } else {
+            for found in find {
+                if let Some(replacement) = args.replace {
+                    writeln!(
+                        writer,
+                        "{prefix}{}-{}",
+                        padded_line(found.line, width, args),
+                        found.text
+                    )?;
+                    let replaced = matcher.replace_all(&found.text, replacement, args.regex);
+                    writeln!(
+                        writer,
+                        "{prefix}{}+{}",
+                        padded_line(found.line, width, args),
+                        replaced
+                    )?;
+                    continue;
+                }
+                let (clipped_found, clipped) =
+                    clip_found(&signature_found(found), args.max_columns);
+                let scope = scope_suffix(found);
+                let count = repeated_suffix(repeated, filename, found.line);
+                writeln!(
+                    writer,
+                    "{prefix}{}:{}{clipped}{count}{scope}",
+                    padded_line(found.line, width, args),
+                    clipped_found.text
+                )?;
+            }
+        }
+        if !args.no_heading {
+            writeln!(writer)?;
+        }
+    }
+    writer.flush()
 }
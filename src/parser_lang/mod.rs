@@ -1,24 +1,66 @@
 /*! the tree-sitter parser to parse the code.
 
-    Now can only support the `c cpp python rust` language
+    Now can only support the `c cpp python rust java csharp bash kotlin swift lua markdown hcl json` language
 */
 
-use tree_sitter::Parser;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+
+use encoding_rs::Encoding;
+use tree_sitter::{InputEdit, ParseOptions, ParseState, Parser, Point, Tree};
 
 /// Should use `&xxx.into()` to set_language
+use tree_sitter_bash::LANGUAGE as bash;
+use tree_sitter_c_sharp::LANGUAGE as csharp;
 use tree_sitter_cpp::LANGUAGE as cpp;
+use tree_sitter_hcl::LANGUAGE as hcl;
+use tree_sitter_java::LANGUAGE as java;
+use tree_sitter_json::LANGUAGE as json;
+use tree_sitter_kotlin_ng::LANGUAGE as kotlin;
+use tree_sitter_lua::LANGUAGE as lua;
+use tree_sitter_md::LANGUAGE as markdown;
 use tree_sitter_python::LANGUAGE as python;
 use tree_sitter_rust::LANGUAGE as rust;
+use tree_sitter_swift::LANGUAGE as swift;
 
-use crate::Options::Lang;
+use crate::{search::error::SearchError, Options::Lang};
 
 /// The parser to parse the code.
 ///
 /// *NOTE:* use tree-sitter to parse code.
+/// The parsed files are cached until their modified time changes, if `enable_cache`.
 #[derive(Default)]
 pub struct SpecifyParser {
     lang: Lang,
     parser: Parser,
+    cache: Option<AstCache>,
+    encoding: Option<&'static Encoding>,
+    timeout: Option<Duration>,
+}
+
+/** The parsed files, shared by the parsers of the threads, see `SpecifyParser::share_cache`.
+
+*NOTE:* a clone is another handle to the same cache.
+*/
+#[derive(Clone, Default)]
+struct AstCache(Arc<Mutex<HashMap<String, CachedAst>>>);
+
+impl AstCache {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, CachedAst>> {
+        // a panicked thread can't leave a half written entry, the map is still good.
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// The source and the ast of a parsed file.
+struct CachedAst {
+    lang: Lang,
+    modified: SystemTime,
+    code: String,
+    tree: Tree,
 }
 
 impl SpecifyParser {
@@ -39,18 +81,215 @@ impl SpecifyParser {
                 .set_language(&python.into())
                 .expect(error_loading_msg),
             Lang::Rust => parser.set_language(&rust.into()).expect(error_loading_msg),
+            Lang::Java => parser.set_language(&java.into()).expect(error_loading_msg),
+            Lang::CSharp => parser
+                .set_language(&csharp.into())
+                .expect(error_loading_msg),
+            Lang::Bash => parser.set_language(&bash.into()).expect(error_loading_msg),
+            Lang::Kotlin => parser
+                .set_language(&kotlin.into())
+                .expect(error_loading_msg),
+            Lang::Swift => parser.set_language(&swift.into()).expect(error_loading_msg),
+            Lang::Lua => parser.set_language(&lua.into()).expect(error_loading_msg),
+            Lang::Markdown => parser
+                .set_language(&markdown.into())
+                .expect(error_loading_msg),
+            Lang::Hcl => parser.set_language(&hcl.into()).expect(error_loading_msg),
+            Lang::Json => parser.set_language(&json.into()).expect(error_loading_msg),
             _ => Default::default(),
         };
 
         SpecifyParser {
             lang: lang.clone(),
             parser,
+            cache: None,
+            encoding: None,
+            timeout: None,
         }
     }
 
+    /// set the time to give up parsing a code, `None` to never give up.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// set the encoding to decode the files, `None` for UTF-8. see `decode`.
+    pub fn set_encoding(&mut self, encoding: Option<&'static Encoding>) {
+        self.encoding = encoding;
+    }
+
     /// use tree-sitter parser to get the ast tree
-    pub fn get_ast(&mut self, code: &str) -> tree_sitter::Tree {
-        self.parser.parse(code, None).unwrap()
+    ///
+    /// *NOTE:* the path of the `SearchError::Parse` is empty, as the code has no path.
+    /// Fails if the parse timed out or no language is set.
+    pub fn get_ast(&mut self, code: &str) -> Result<Tree, SearchError> {
+        let tree = self.parse(code, None);
+        self.parsed(tree)
+    }
+
+    /// parse the code, giving up once the timeout is passed.
+    fn parse(&mut self, code: &str, old_tree: Option<&Tree>) -> Option<Tree> {
+        let Some(timeout) = self.timeout else {
+            return self.parser.parse(code, old_tree);
+        };
+        let start = Instant::now();
+        let mut timed_out = |_: &ParseState| start.elapsed() > timeout;
+        let options = ParseOptions::new().progress_callback(&mut timed_out);
+        let bytes = code.as_bytes();
+        self.parser.parse_with_options(
+            &mut |byte, _| bytes.get(byte..).unwrap_or_default(),
+            old_tree,
+            Some(options),
+        )
+    }
+
+    /// the parsed tree, or the error if the parse failed.
+    ///
+    /// *NOTE:* the parser is reset after a timed out parse,
+    /// or the next parse would resume the unfinished one, even of another code.
+    fn parsed(&mut self, tree: Option<Tree>) -> Result<Tree, SearchError> {
+        tree.ok_or_else(|| {
+            self.parser.reset();
+            SearchError::Parse {
+                path: String::new(),
+            }
+        })
+    }
+
+    /** re-parse the code after an edit, reusing the unchanged parts of the old tree.
+
+    *PARAM:* {old_tree} The tree of the code before the edit, it's not changed.
+
+    *PARAM:* {edit} The edit turning the old code into `code`, see `input_edit`.
+
+    ```rust
+    use search_code::{parser_lang::{input_edit, SpecifyParser}, Options::Lang};
+
+    let mut parser = SpecifyParser::from_lang(&Lang::Rust);
+    let old_code = "fn main() {}\n";
+    let old_tree = parser.get_ast(old_code).unwrap();
+    let code = "fn main() {}\nfn other() {}\n";
+    let edit = input_edit(old_code, code).unwrap();
+    let tree = parser.get_ast_incremental(code, &old_tree, &edit).unwrap();
+    assert_eq!(tree.root_node().named_child_count(), 2);
+    ```
+    */
+    pub fn get_ast_incremental(
+        &mut self,
+        code: &str,
+        old_tree: &Tree,
+        edit: &InputEdit,
+    ) -> Result<Tree, SearchError> {
+        let mut old_tree = old_tree.clone();
+        old_tree.edit(edit);
+        let tree = self.parse(code, Some(&old_tree));
+        self.parsed(tree)
+    }
+
+    /// read the file and get the source code and the ast tree.
+    ///
+    /// *NOTE:* with the cache enabled, reuse the cached one if the file is not modified
+    /// since the last parse, or re-parse it incrementally from the cached one if modified.
+    /// Without the cache, the file is parsed each time and nothing is kept.
+    pub fn get_file_ast(&mut self, filename: &str) -> Result<(String, Tree), SearchError> {
+        let Some(cache) = self.cache.clone() else {
+            let code = self.read_code(filename)?;
+            let tree = self.get_ast(&code).map_err(|_| SearchError::Parse {
+                path: filename.to_string(),
+            })?;
+            return Ok((code, tree));
+        };
+        let modified = std::fs::metadata(filename)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|err| SearchError::io(filename, err))?;
+        let cached = {
+            let mut cache = cache.lock();
+            match cache.get(filename) {
+                Some(cached) if cached.lang == self.lang && cached.modified == modified => {
+                    return Ok((cached.code.clone(), cached.tree.clone()));
+                }
+                // the same file parsed as another language isn't reused.
+                _ => cache
+                    .remove(filename)
+                    .filter(|cached| cached.lang == self.lang),
+            }
+        };
+
+        let code = self.read_code(filename)?;
+        let edit = cached
+            .as_ref()
+            .and_then(|cached| Some((cached, input_edit(&cached.code, &code)?)));
+        let tree = match edit {
+            Some((cached, edit)) => self.get_ast_incremental(&code, &cached.tree, &edit),
+            None => self.get_ast(&code),
+        }
+        .map_err(|_| SearchError::Parse {
+            path: filename.to_string(),
+        })?;
+        cache.lock().insert(
+            filename.to_string(),
+            CachedAst {
+                lang: self.lang.clone(),
+                modified,
+                code: code.clone(),
+                tree: tree.clone(),
+            },
+        );
+        Ok((code, tree))
+    }
+
+    /// read the file and decode it, see `decode`.
+    fn read_code(&self, filename: &str) -> Result<String, SearchError> {
+        let bytes = std::fs::read(filename).map_err(|err| SearchError::io(filename, err))?;
+        Ok(decode(bytes, self.encoding))
+    }
+
+    /// keep the parsed files to reuse them in the next parses, like in the watch mode.
+    ///
+    /// *NOTE:* the cache isn't bounded, it's off by default so a one-shot search
+    /// doesn't keep every file it parsed.
+    pub fn enable_cache(&mut self) {
+        self.cache.get_or_insert_with(AstCache::default);
+    }
+
+    /// drop all the cached ast trees, of the parsers sharing the cache too.
+    pub fn clear_cache(&mut self) {
+        if let Some(cache) = &self.cache {
+            cache.lock().clear();
+        }
+    }
+
+    /** share the cache of the parsed files with the other parser,
+    like the parsers of the threads in a parallel search.
+
+    *NOTE:* this parser caches nothing if the other one has no cache.
+
+    ```rust
+    use search_code::{parser_lang::SpecifyParser, Options::Lang};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("share_cache.rs");
+    std::fs::write(&path, "fn main() {}\n").unwrap();
+    let path = path.to_str().unwrap();
+
+    let mut parser = SpecifyParser::from_lang(&Lang::Rust);
+    parser.enable_cache();
+    parser.get_file_ast(path).unwrap();
+    // change the file but keep its modified time, so only a cached parse gives the old code.
+    let modified = std::fs::metadata(path).unwrap().modified().unwrap();
+    std::fs::write(path, "fn other() {}\n").unwrap();
+    let file = std::fs::File::options().write(true).open(path).unwrap();
+    file.set_modified(modified).unwrap();
+
+    let mut other = SpecifyParser::from_lang(&Lang::Rust);
+    other.share_cache(&parser);
+    assert_eq!(other.get_file_ast(path).unwrap().0, "fn main() {}\n");
+    let mut alone = SpecifyParser::from_lang(&Lang::Rust);
+    assert_eq!(alone.get_file_ast(path).unwrap().0, "fn other() {}\n");
+    ```
+    */
+    pub fn share_cache(&mut self, other: &SpecifyParser) {
+        self.cache = other.cache.clone();
     }
 
     /// get the language of the parser
@@ -60,3 +299,111 @@ impl SpecifyParser {
         &self.lang
     }
 }
+
+/** Decode the bytes of a file to the code.
+
+*NOTE:* the bytes are decoded from the encoding if set, a BOM overrides it.
+Or the bytes are UTF-8, and the invalid ones are replaced by `U+FFFD`.
+
+```rust
+use search_code::parser_lang::decode;
+
+let shift_jis = encoding_rs::Encoding::for_label(b"shift_jis");
+assert_eq!(decode(vec![0x93, 0xfa, 0x96, 0x7b], shift_jis), "日本");
+assert_eq!(decode(b"fn \xff()".to_vec(), None), "fn \u{fffd}()");
+```
+*/
+pub fn decode(bytes: Vec<u8>, encoding: Option<&'static Encoding>) -> String {
+    match encoding {
+        Some(encoding) => encoding.decode(&bytes).0.into_owned(),
+        None => String::from_utf8(bytes)
+            .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned()),
+    }
+}
+
+/** Get the edit turning the old code into the new code, as one changed range
+between the common prefix and the common suffix.
+
+*Return:* `None` if the codes are the same.
+
+```rust
+use search_code::parser_lang::input_edit;
+
+let edit = input_edit("a\nbc\n", "a\nbxc\n").unwrap();
+assert_eq!((edit.start_byte, edit.old_end_byte, edit.new_end_byte), (3, 3, 4));
+assert_eq!((edit.start_position.row, edit.start_position.column), (1, 1));
+assert!(input_edit("same", "same").is_none());
+```
+*/
+pub fn input_edit(old_code: &str, code: &str) -> Option<InputEdit> {
+    if old_code == code {
+        return None;
+    }
+    let (old, new) = (old_code.as_bytes(), code.as_bytes());
+    let mut start = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    // keep the edit on the char boundaries.
+    while !old_code.is_char_boundary(start) || !code.is_char_boundary(start) {
+        start -= 1;
+    }
+    let max_suffix = old.len().min(new.len()) - start;
+    let mut suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old_code.is_char_boundary(old.len() - suffix)
+        || !code.is_char_boundary(new.len() - suffix)
+    {
+        suffix -= 1;
+    }
+    let (old_end, new_end) = (old.len() - suffix, new.len() - suffix);
+    Some(InputEdit {
+        start_byte: start,
+        old_end_byte: old_end,
+        new_end_byte: new_end,
+        start_position: point_at(old_code, start),
+        old_end_position: point_at(old_code, old_end),
+        new_end_position: point_at(code, new_end),
+    })
+}
+
+/** Get the positions of the syntax errors in the tree, the `ERROR` and the missing nodes.
+
+*NOTE:* tree-sitter still gives a tree for the invalid code, the errors are in it.
+The nodes inside an `ERROR` node are not checked again.
+
+```rust
+use search_code::{parser_lang::{parse_errors, SpecifyParser}, Options::Lang};
+
+let mut parser = SpecifyParser::from_lang(&Lang::Rust);
+let tree = parser.get_ast("fn ok() {}\nfn broken( {\n").unwrap();
+let errors = parse_errors(&tree);
+assert!(!errors.is_empty());
+assert_eq!(errors[0].row, 1);
+assert!(parse_errors(&parser.get_ast("fn ok() {}\n").unwrap()).is_empty());
+```
+*/
+pub fn parse_errors(tree: &Tree) -> Vec<Point> {
+    let mut errors = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if node.is_error() || node.is_missing() {
+            errors.push(node.start_position());
+        } else if node.has_error() {
+            let mut cursor = node.walk();
+            let children: Vec<_> = node.children(&mut cursor).collect();
+            stack.extend(children.into_iter().rev());
+        }
+    }
+    errors
+}
+
+/// The row and the byte column of the byte offset in the code.
+fn point_at(code: &str, byte: usize) -> Point {
+    let before = &code.as_bytes()[..byte];
+    let row = before.iter().filter(|&&c| c == b'\n').count();
+    let column = before.iter().rev().take_while(|&&c| c != b'\n').count();
+    Point::new(row, column)
+}
@@ -0,0 +1,197 @@
+/*!
+    Build the search with the chainable methods, instead of the `IgnoreDir` and the
+    `SearchOptions` by hand.
+*/
+
+use std::path::Path;
+
+use super::{error::SearchError, FindLines, SearchCode, SearchOptions};
+use crate::Options::{IgnoreDir, Lang, SymbolMatch};
+
+/** The builder of the configured `SearchCode`.
+
+*NOTE:* the git dir is ignored and the progress bar is hidden by default, like a library
+should be quiet. The other options are the defaults of `SearchOptions`.
+
+*Use:*
+
+```rust
+use search_code::{search::builder::SearchBuilder, Options::Lang};
+
+let dir = tempfile::tempdir().unwrap();
+let dir = dir.path();
+std::fs::create_dir_all(dir.join("target")).unwrap();
+std::fs::write(dir.join("main.rs"), "fn main() {\n    Run();\n}\n").unwrap();
+std::fs::write(dir.join("target/gen.rs"), "fn run() {}\n").unwrap();
+
+let mut search = SearchBuilder::new()
+    .lang(Lang::Rust)
+    .ignore(vec![dir.join("target").to_str().unwrap().to_string()])
+    .case_insensitive(true)
+    .regex(true)
+    .build();
+let found = search.run(dir, r"run\(").unwrap();
+let lines = &found[dir.join("main.rs").to_str().unwrap()];
+assert_eq!(found.len(), 1);
+assert_eq!((lines[0].line, lines[0].col), (2, 5));
+```
+
+The symbols:
+
+```rust
+use search_code::{search::builder::SearchBuilder, Options::{Lang, SymbolMatch}};
+
+let dir = tempfile::tempdir().unwrap();
+let dir = dir.path();
+std::fs::write(dir.join("lib.rs"), "fn parse_header() {}\nfn parse_body() {}\n").unwrap();
+
+let mut search = SearchBuilder::new()
+    .lang(Lang::Rust)
+    .symbol(true)
+    .symbol_match(SymbolMatch::Prefix)
+    .build();
+let found = search.run(dir, "parse_").unwrap();
+let symbols: Vec<_> = found.values().flatten().filter_map(|found| found.symbol.as_deref()).collect();
+assert_eq!(symbols, ["parse_header", "parse_body"]);
+```
+*/
+#[derive(Debug, Clone)]
+pub struct SearchBuilder {
+    langs: Vec<Lang>,
+    ignores: Vec<String>,
+    ignore_git: bool,
+    options: SearchOptions,
+}
+
+impl Default for SearchBuilder {
+    fn default() -> Self {
+        Self {
+            langs: vec![Lang::Any],
+            ignores: vec![],
+            ignore_git: true,
+            options: SearchOptions {
+                quiet: true,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl SearchBuilder {
+    /// Search any files, see `Default`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only search the files of the language.
+    pub fn lang(mut self, lang: Lang) -> Self {
+        self.langs = vec![lang];
+        self
+    }
+
+    /// Only search the files of any of the languages.
+    pub fn langs(mut self, langs: Vec<Lang>) -> Self {
+        self.langs = langs;
+        self
+    }
+
+    /// The dirs and the files to skip, the relative ones are fixed to start with `./`.
+    pub fn ignore(mut self, dir_and_files: Vec<String>) -> Self {
+        self.ignores = dir_and_files;
+        self
+    }
+
+    /// Skip the git dir and files, `true` by default.
+    pub fn ignore_git(mut self, ignore_git: bool) -> Self {
+        self.ignore_git = ignore_git;
+        self
+    }
+
+    /// Search the symbols with tree-sitter instead of the raw text.
+    pub fn symbol(mut self, is_symbol: bool) -> Self {
+        self.options.is_symbol = is_symbol;
+        self
+    }
+
+    /// How the key matches the symbol names in the symbol search.
+    pub fn symbol_match(mut self, symbol_match: SymbolMatch) -> Self {
+        self.options.symbol_match = symbol_match;
+        self
+    }
+
+    /// The key matches regardless of the case in the raw text search.
+    pub fn case_insensitive(mut self, ignore_case: bool) -> Self {
+        self.options.ignore_case = ignore_case;
+        self
+    }
+
+    /// The key is a regex in the raw text search, instead of a literal text.
+    pub fn regex(mut self, regex: bool) -> Self {
+        self.options.regex = regex;
+        self
+    }
+
+    /// The key only matches the whole words in the raw text search.
+    pub fn word(mut self, word: bool) -> Self {
+        self.options.word = word;
+        self
+    }
+
+    /// Detect the language of each file, instead of using the given one.
+    pub fn auto(mut self, auto: bool) -> Self {
+        self.options.auto = auto;
+        self
+    }
+
+    /// Stop reading a file after `max_count` lines found.
+    pub fn max_count(mut self, max_count: usize) -> Self {
+        self.options.max_count = Some(max_count);
+        self
+    }
+
+    /// Stop the whole search after `max_total` lines found.
+    pub fn max_total(mut self, max_total: usize) -> Self {
+        self.options.max_total = Some(max_total);
+        self
+    }
+
+    /// Draw the progress bar and the warnings, hidden by default.
+    pub fn progress(mut self, progress: bool) -> Self {
+        self.options.quiet = !progress;
+        self
+    }
+
+    /// Keep the parsed files to reuse them when the same files are searched again.
+    pub fn cache_asts(mut self, cache_asts: bool) -> Self {
+        self.options.cache_asts = cache_asts;
+        self
+    }
+
+    /// Replace all the options, for the ones without a method.
+    ///
+    /// *NOTE:* the methods called after it still set their options.
+    pub fn options(mut self, options: SearchOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Build the configured search.
+    pub fn build(self) -> SearchCode {
+        let mut ignore_dir = IgnoreDir::new();
+        ignore_dir.set_ignores(self.ignores);
+        if self.ignore_git {
+            ignore_dir.ignore_git();
+        }
+        ignore_dir.relative_dir_fix();
+        let mut search = SearchCode::build_langs(&self.langs, ignore_dir);
+        search.set_options(self.options);
+        search
+    }
+}
+
+impl SearchCode {
+    /// Search the key in the path, a file or a directory, see `SearchCode::search_all`.
+    pub fn run(&mut self, path: impl AsRef<Path>, key: &str) -> Result<FindLines, SearchError> {
+        self.search_all(&[path.as_ref()], key)
+    }
+}
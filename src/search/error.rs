@@ -0,0 +1,86 @@
+/*!
+    The error of the search, each error carries the path that failed.
+*/
+
+use std::{error::Error, fmt, io, path::Path};
+
+use super::matcher::MatcherError;
+use crate::Options::Lang;
+
+/// The error returned by the search functions.
+#[derive(Debug)]
+pub enum SearchError {
+    /// Can't read the file or the directory.
+    Io { path: String, source: io::Error },
+    /// tree-sitter can't parse the file.
+    Parse { path: String },
+    /// The language can't be used to search the symbols.
+    UnsupportedLanguage { path: String, lang: Lang },
+    /// The tree-sitter query can't be compiled.
+    InvalidQuery {
+        path: String,
+        source: tree_sitter::QueryError,
+    },
+    /// The key can't be compiled to a regex.
+    InvalidRegex { path: String, source: MatcherError },
+}
+
+impl SearchError {
+    /// Wrap the io error with the path that failed.
+    pub fn io(path: &str, source: io::Error) -> Self {
+        SearchError::Io {
+            path: path.to_string(),
+            source,
+        }
+    }
+
+    /// The path is not valid UTF-8, so it can't be searched.
+    pub fn invalid_path(path: &Path) -> Self {
+        SearchError::Io {
+            path: path.display().to_string(),
+            source: io::Error::new(io::ErrorKind::InvalidData, "the path is not valid UTF-8"),
+        }
+    }
+
+    /// The path that failed.
+    pub fn path(&self) -> &str {
+        match self {
+            SearchError::Io { path, .. }
+            | SearchError::Parse { path }
+            | SearchError::UnsupportedLanguage { path, .. }
+            | SearchError::InvalidQuery { path, .. }
+            | SearchError::InvalidRegex { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::Io { path, source } => write!(f, "Can't read {path}: {source}"),
+            SearchError::Parse { path } => write!(f, "Can't parse {path}"),
+            SearchError::UnsupportedLanguage { path, lang } => write!(
+                f,
+                "Can't search the symbols of {path}: the language {lang:?} is not supported. \
+                 Please use -l to specify the language."
+            ),
+            SearchError::InvalidQuery { path, source } => {
+                write!(f, "Invalid query for {path}: {source}")
+            }
+            SearchError::InvalidRegex { path, source } => {
+                write!(f, "Invalid regex for {path}: {source}")
+            }
+        }
+    }
+}
+
+impl Error for SearchError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SearchError::Io { source, .. } => Some(source),
+            SearchError::InvalidQuery { source, .. } => Some(source),
+            SearchError::InvalidRegex { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,108 @@
+/*!
+    Find and color the matches of the key in a line, like the printed result.
+*/
+
+use std::ops::Range;
+
+use colored::{Color, Colorize};
+
+use super::{matcher::KeyMatcher, SearchOptions};
+
+/** Find the byte ranges of the matches in the line, the empty matches are skipped.
+
+*NOTE:* the matches never overlap, each one starts after the end of the last one.
+The ranges are always on the char boundaries.
+
+```rust
+use regex::Regex;
+use search_code::search::{highlight::search_one_line, matcher::KeyMatcher};
+
+let matcher = KeyMatcher::from(Regex::new("aa").unwrap());
+assert_eq!(search_one_line("aaaaa", &matcher), vec![0..2, 2..4]);
+let matcher = KeyMatcher::from(Regex::new("a*").unwrap());
+assert!(search_one_line("b", &matcher).is_empty());
+```
+*/
+pub fn search_one_line(line: &str, matcher: &KeyMatcher) -> Vec<Range<usize>> {
+    matcher
+        .find_iter(line)
+        .filter(|range| !range.is_empty())
+        .collect()
+}
+
+/** Color each match of the literal key in the line.
+
+*NOTE:* the line is returned as is if the key is empty.
+Use `highlight_with` for the regex, the whole word or the case-insensitive key.
+
+```rust
+use colored::{Color, Colorize};
+use search_code::search::highlight::highlight;
+
+colored::control::set_override(true);
+// the overlapping matches are colored from the left.
+assert_eq!(highlight("aaa", "aa", Color::Red), format!("{}a", "aa".red()));
+assert_eq!(highlight("key", "", Color::Red), "key");
+// the multibyte chars are never split.
+assert_eq!(
+    highlight("let 名前 = \"名\";", "名", Color::Red),
+    format!("let {}前 = \"{}\";", "名".red(), "名".red())
+);
+assert_eq!(highlight("é", "e", Color::Red), "é");
+```
+*/
+pub fn highlight(line: &str, key: &str, color: Color) -> String {
+    if key.is_empty() {
+        return line.to_string();
+    }
+    match SearchOptions::default().key_matcher(key) {
+        Ok(matcher) => highlight_with(line, &matcher, color),
+        Err(_) => line.to_string(),
+    }
+}
+
+/** Color each match of the key in the line, the match spans of `--pcre` too.
+
+*NOTE:* find all the match ranges in the raw line first, then build the colored line
+from them, so the colored key is never matched again.
+
+```rust
+use colored::{Color, Colorize};
+use search_code::search::{highlight::highlight_with, SearchOptions};
+
+colored::control::set_override(true);
+let options = SearchOptions {
+    regex: true,
+    word: true,
+    ignore_case: true,
+    ..Default::default()
+};
+let matcher = options.key_matcher("fo+").unwrap();
+assert_eq!(
+    highlight_with("Foo food fOO", &matcher, Color::Red),
+    format!("{} food {}", "Foo".red(), "fOO".red())
+);
+// the lookbehind of `--pcre`.
+let options = SearchOptions {
+    regex: true,
+    pcre: true,
+    ..Default::default()
+};
+let matcher = options.key_matcher(r"(?<=\.)run").unwrap();
+assert_eq!(
+    highlight_with("run(); self.run();", &matcher, Color::Red),
+    format!("run(); self.{}();", "run".red())
+);
+```
+*/
+pub fn highlight_with(line: &str, matcher: &KeyMatcher, color: Color) -> String {
+    let mut highlighted = String::with_capacity(line.len());
+    let mut last = 0;
+    for range in search_one_line(line, matcher) {
+        highlighted.push_str(&line[last..range.start]);
+        highlighted.push_str(&line[range.clone()].color(color).to_string());
+        last = range.end;
+    }
+    highlighted.push_str(&line[last..]);
+    highlighted
+}
@@ -0,0 +1,242 @@
+/*!
+    The symbol index, all the symbols of the files with the files' last modified times.
+
+    Built once by `--index`, then `--use-index` finds the symbols from it without parsing
+    the files again.
+*/
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    path::Path,
+    time::SystemTime,
+};
+
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::{error::SearchError, FindLines, FoundLine, SearchCode, SearchOptions};
+use crate::Options::SymbolMatch;
+
+/// A symbol in the index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedSymbol {
+    pub file: String,
+    /// the 1-based line number.
+    pub line: usize,
+    /// the 1-based byte column where the symbol starts in the line.
+    pub col: usize,
+    /// the line contains the symbol.
+    pub text: String,
+    /// the kind of the symbol, like `function` or `struct`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+}
+
+/** The symbols of the files, serialized to JSON.
+
+*NOTE:* only the files with any symbol are indexed.
+
+```rust
+use search_code::{
+    parser_lang::SpecifyParser,
+    search::{get_symbols, index::SymbolIndex, SearchOptions},
+    Options::Lang,
+};
+
+let dir = tempfile::tempdir().unwrap();
+let dir = dir.path();
+let file = dir.join("a.rs");
+std::fs::write(&file, "fn parse_header() {}\nfn parse_body() {}\n").unwrap();
+let file = file.to_str().unwrap();
+
+let options = SearchOptions {
+    is_symbol: true,
+    list_symbols: true,
+    ..Default::default()
+};
+let found = get_symbols(file, "", &mut SpecifyParser::from_lang(&Lang::Rust), &options).unwrap();
+let index_file = dir.join("index.json");
+SymbolIndex::from_found(&found).save(&index_file).unwrap();
+
+let index = SymbolIndex::load(&index_file).unwrap();
+assert!(index.stale_files().is_empty());
+let found = index.find("_body$", &SearchOptions::default()).unwrap();
+assert_eq!(found[file][0].symbol.as_deref(), Some("parse_body"));
+assert_eq!(found[file][0].line, 2);
+```
+*/
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SymbolIndex {
+    /// filename -> the last modified time when indexed.
+    files: BTreeMap<String, SystemTime>,
+    /// symbol name -> the places of the symbol.
+    symbols: BTreeMap<String, Vec<IndexedSymbol>>,
+    /// the index file, the path of the errors.
+    #[serde(skip)]
+    path: String,
+}
+
+impl SymbolIndex {
+    /// Index the found symbols, like the ones found by `--list-symbols`.
+    ///
+    /// *NOTE:* the file that can't be read now is not indexed.
+    pub fn from_found(found: &FindLines) -> Self {
+        let mut index = SymbolIndex::default();
+        for (filename, lines) in found {
+            let Some(modified) = modified_time(filename) else {
+                continue;
+            };
+            index.files.insert(filename.clone(), modified);
+            for found in lines {
+                let Some(symbol) = &found.symbol else {
+                    continue;
+                };
+                index
+                    .symbols
+                    .entry(symbol.clone())
+                    .or_default()
+                    .push(IndexedSymbol {
+                        file: filename.clone(),
+                        line: found.line,
+                        col: found.col,
+                        text: found.text.clone(),
+                        kind: found.kind.clone(),
+                    });
+            }
+        }
+        index
+    }
+
+    /// Read the index from the JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, io::Error> {
+        let file = BufReader::new(File::open(path.as_ref())?);
+        let mut index: SymbolIndex = serde_json::from_reader(file)?;
+        index.path = path.as_ref().display().to_string();
+        Ok(index)
+    }
+
+    /// Write the index to the JSON file, the file will be created or truncated.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), io::Error> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut writer, self)?;
+        writer.flush()
+    }
+
+    /// The count of the indexed files.
+    pub fn files(&self) -> usize {
+        self.files.len()
+    }
+
+    /// The count of the indexed symbols.
+    pub fn symbols(&self) -> usize {
+        self.symbols.values().map(Vec::len).sum()
+    }
+
+    /// The indexed files modified or removed since indexing, their symbols are outdated.
+    pub fn stale_files(&self) -> Vec<&str> {
+        self.files
+            .iter()
+            .filter(|(filename, modified)| modified_time(filename).as_ref() != Some(*modified))
+            .map(|(filename, _)| filename.as_str())
+            .collect()
+    }
+
+    /** Find the indexed symbols matching the key, like the symbol search.
+
+    *NOTE:* the symbols of the stale files are skipped, see `stale_files`.
+    `options.symbol_match`, `options.invert_symbol`, `options.exclude_symbol` and
+    `options.list_symbols` are used like `get_symbols`.
+    */
+    pub fn find(&self, key: &str, options: &SearchOptions) -> Result<FindLines, SearchError> {
+        let stale: HashSet<&str> = self.stale_files().into_iter().collect();
+        let list_all = options.list_symbols || (options.invert_symbol && key.is_empty());
+        let fuzzy = options.symbol_match == SymbolMatch::Fuzzy && !list_all;
+        let matcher = if list_all || fuzzy {
+            None
+        } else {
+            Some(
+                Regex::new(&options.symbol_match.regex(key)).map_err(|source| {
+                    SearchError::InvalidRegex {
+                        path: self.path.clone(),
+                        source: source.into(),
+                    }
+                })?,
+            )
+        };
+        let fuzzy_matcher = SkimMatcherV2::default();
+        let mut found = BTreeMap::new();
+        for (name, symbols) in &self.symbols {
+            if options.exclude_symbol.iter().any(|v| v == name) {
+                continue;
+            }
+            let score = if fuzzy {
+                let Some(score) = fuzzy_matcher.fuzzy_match(name, key) else {
+                    continue;
+                };
+                Some(score)
+            } else {
+                None
+            };
+            if matcher
+                .as_ref()
+                .is_some_and(|matcher| matcher.is_match(name) == options.invert_symbol)
+            {
+                continue;
+            }
+            for symbol in symbols {
+                if stale.contains(symbol.file.as_str()) {
+                    continue;
+                }
+                found
+                    .entry(symbol.file.clone())
+                    .or_insert_with(Vec::new)
+                    .push(FoundLine {
+                        line: symbol.line,
+                        col: symbol.col,
+                        byte_offset: None,
+                        byte_end: None,
+                        text: symbol.text.clone(),
+                        symbol: Some(name.clone()),
+                        scope: None,
+                        body_lines: None,
+                        key_path: None,
+                        kind: symbol.kind.clone(),
+                        signature: None,
+                        next_symbols: None,
+                        score,
+                    });
+            }
+        }
+        for find in found.values_mut() {
+            find.sort_by_key(|found: &FoundLine| (found.line, found.col));
+        }
+        Ok(found)
+    }
+}
+
+impl SearchCode {
+    /// Find the symbols in the index, and search the stale files again, see `SymbolIndex::find`.
+    ///
+    /// *NOTE:* the removed files are dropped, and the files added since indexing are not found.
+    pub fn search_index(
+        &mut self,
+        index: &SymbolIndex,
+        key: &str,
+    ) -> Result<FindLines, SearchError> {
+        let mut found = index.find(key, &self.options)?;
+        for file in index.stale_files() {
+            if Path::new(file).is_file() {
+                found.extend(self.search(Path::new(file), key)?);
+            }
+        }
+        Ok(found)
+    }
+}
+
+/// The last modified time of the file, `None` if it can't be read.
+fn modified_time(filename: &str) -> Option<SystemTime> {
+    fs::metadata(filename).and_then(|meta| meta.modified()).ok()
+}
@@ -0,0 +1,190 @@
+/*!
+    Match the key in the raw text with the `regex` engine, or the `fancy-regex` engine for `--pcre`.
+
+    The `regex` engine is fast and never backtracks, but has no lookaround and no backreferences.
+    The `fancy-regex` engine supports them by backtracking, so it can be much slower,
+    and gives up the line after too many steps.
+*/
+
+use std::{borrow::Cow, error::Error, fmt, ops::Range};
+
+use regex::Regex;
+
+/** The compiled key, the match ranges are the byte ranges in the text.
+
+```rust
+use search_code::search::SearchOptions;
+
+let options = SearchOptions {
+    regex: true,
+    pcre: true,
+    ..Default::default()
+};
+// the `fn` only followed by `main`.
+let matcher = options.key_matcher(r"fn(?= main)").unwrap();
+assert_eq!(matcher.find("fn helper() {} fn main() {}"), Some(15..17));
+assert!(!matcher.is_match("fn helper() {}"));
+assert_eq!(matcher.replace_all("fn main", "func", false), "func main");
+```
+*/
+#[derive(Debug, Clone)]
+pub enum KeyMatcher {
+    /// The default `regex` engine.
+    Regex(Regex),
+    /// The `fancy-regex` engine, with lookaround and backreferences.
+    Pcre(fancy_regex::Regex),
+}
+
+impl From<Regex> for KeyMatcher {
+    fn from(regex: Regex) -> Self {
+        KeyMatcher::Regex(regex)
+    }
+}
+
+impl KeyMatcher {
+    /// The range of the first match in the text.
+    ///
+    /// *NOTE:* the `fancy-regex` error like too much backtracking is treated as no match.
+    pub fn find(&self, text: &str) -> Option<Range<usize>> {
+        match self {
+            KeyMatcher::Regex(regex) => regex.find(text).map(|matched| matched.range()),
+            KeyMatcher::Pcre(regex) => regex.find(text).ok()?.map(|matched| matched.range()),
+        }
+    }
+
+    /// Check if the key matches the text anywhere.
+    pub fn is_match(&self, text: &str) -> bool {
+        self.find(text).is_some()
+    }
+
+    /// The ranges of all the matches in the text, the empty ones included.
+    pub fn find_iter<'a>(&'a self, text: &'a str) -> Box<dyn Iterator<Item = Range<usize>> + 'a> {
+        match self {
+            KeyMatcher::Regex(regex) => {
+                Box::new(regex.find_iter(text).map(|matched| matched.range()))
+            }
+            KeyMatcher::Pcre(regex) => Box::new(
+                regex
+                    .find_iter(text)
+                    .map_while(Result::ok)
+                    .map(|matched| matched.range()),
+            ),
+        }
+    }
+
+    /** The range of each match in the text, with its replacement.
+
+    *NOTE:* with `expand`, the `$1` and the `${name}` in the replacement are expanded to
+    the captured groups, and `$$` to a literal `$`, like `Regex::replace_all`.
+    Or the replacement is literal, `$` is never expanded.
+
+    *NOTE:* the empty matches are skipped, like the ones of `o*` between the other chars,
+    so the preview and the written files replace the same matches.
+
+    ```rust
+    use search_code::search::SearchOptions;
+
+    let options = SearchOptions {
+        regex: true,
+        ..Default::default()
+    };
+    let matcher = options.key_matcher(r"fn (?<name>\w+)").unwrap();
+    let replaced = matcher.replacements("fn a() {} fn b() {}", "function ${name}$$", true);
+    assert_eq!(replaced[0], (0..4, "function a$".into()));
+    assert_eq!(replaced[1], (10..14, "function b$".into()));
+
+    let matcher = options.key_matcher("o*").unwrap();
+    assert!(matcher.replacements("abc", "X", false).is_empty());
+    assert_eq!(matcher.replace_all("foo", "X", false), "fX");
+    ```
+    */
+    pub fn replacements<'a>(
+        &self,
+        text: &str,
+        replacement: &'a str,
+        expand: bool,
+    ) -> Vec<(Range<usize>, Cow<'a, str>)> {
+        if !expand {
+            return self
+                .find_iter(text)
+                .filter(|range| !range.is_empty())
+                .map(|range| (range, Cow::Borrowed(replacement)))
+                .collect();
+        }
+        let mut replacements: Vec<_> = match self {
+            KeyMatcher::Regex(regex) => regex
+                .captures_iter(text)
+                .map(|captures| {
+                    let mut expanded = String::new();
+                    captures.expand(replacement, &mut expanded);
+                    (captures.get(0).unwrap().range(), Cow::Owned(expanded))
+                })
+                .collect(),
+            KeyMatcher::Pcre(regex) => regex
+                .captures_iter(text)
+                .map_while(Result::ok)
+                .map(|captures| {
+                    let mut expanded = String::new();
+                    captures.expand(replacement, &mut expanded);
+                    (captures.get(0).unwrap().range(), Cow::Owned(expanded))
+                })
+                .collect(),
+        };
+        replacements.retain(|(range, _)| !range.is_empty());
+        replacements
+    }
+
+    /// Replace all the matches in the text, the `$` is expanded if `expand`, see `replacements`.
+    pub fn replace_all<'a>(&self, text: &'a str, replacement: &str, expand: bool) -> Cow<'a, str> {
+        let replacements = self.replacements(text, replacement, expand);
+        if replacements.is_empty() {
+            return Cow::Borrowed(text);
+        }
+        let mut replaced = String::with_capacity(text.len());
+        let mut last = 0;
+        for (range, replacement) in replacements {
+            replaced.push_str(&text[last..range.start]);
+            replaced.push_str(&replacement);
+            last = range.end;
+        }
+        replaced.push_str(&text[last..]);
+        Cow::Owned(replaced)
+    }
+}
+
+/// The key can't be compiled by the engine.
+#[derive(Debug)]
+pub enum MatcherError {
+    Regex(regex::Error),
+    Pcre(Box<fancy_regex::Error>),
+}
+
+impl From<regex::Error> for MatcherError {
+    fn from(err: regex::Error) -> Self {
+        MatcherError::Regex(err)
+    }
+}
+
+impl From<fancy_regex::Error> for MatcherError {
+    fn from(err: fancy_regex::Error) -> Self {
+        MatcherError::Pcre(Box::new(err))
+    }
+}
+
+impl fmt::Display for MatcherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatcherError::Regex(err) => write!(f, "{err}"),
+            MatcherError::Pcre(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl Error for MatcherError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MatcherError::Regex(err) => Some(err),
+            MatcherError::Pcre(err) => Some(err.as_ref()),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+/*!
+    Search the key in the memory-mapped file, for the raw text search of the large files.
+
+    The whole file is searched at once, the line of each match is found around it,
+    so no line is read into a `String` unless it matches.
+*/
+
+use std::fs::File;
+
+use memmap2::Mmap;
+
+use super::{error::SearchError, FoundLine, SearchOptions};
+
+/// The files smaller than it are read line by line, mapping them is slower.
+pub const MMAP_MIN_SIZE: u64 = 1024 * 1024;
+
+/** Map the file and find the lines containing the key, like the buffered raw text search.
+
+*NOTE:* fall back to the buffered search by returning `None` if the file is smaller than
+`MMAP_MIN_SIZE`, can't be mapped, is not UTF-8, the lines need to be read in order
+for `line_range`, `preceded_by` or `followed_by`, or `pcre` or `encoding` is set.
+
+*Return:* The found lines, or `None` to search the file line by line.
+
+```rust
+use search_code::search::{mmap::{find_key_mapped, MMAP_MIN_SIZE}, SearchOptions};
+
+let dir = tempfile::tempdir().unwrap();
+let path = dir.path().join("mmap.log");
+let mut log = "info: started\r\n".repeat(MMAP_MIN_SIZE as usize / 15);
+log.push_str("error: failed\nwarn: slow\n");
+std::fs::write(&path, &log).unwrap();
+let path = path.to_str().unwrap();
+
+let options = SearchOptions {
+    regex: true,
+    ..Default::default()
+};
+let found = find_key_mapped(path, "^(error|warn):", &options).unwrap().unwrap();
+assert_eq!(found.len(), 2);
+assert_eq!(found[0].line, MMAP_MIN_SIZE as usize / 15 + 1);
+assert_eq!(found[0].text, "error: failed");
+assert_eq!(found[1].byte_offset, Some(log.len() - "warn: slow\n".len()));
+// the `\r` of the CRLF line ending is not in the line, like the buffered search.
+let found = find_key_mapped(path, "started$", &options).unwrap().unwrap();
+assert_eq!(found[0].text, "info: started");
+```
+*/
+pub fn find_key_mapped(
+    filename: &str,
+    key: &str,
+    options: &SearchOptions,
+) -> Result<Option<Vec<FoundLine>>, SearchError> {
+    if options.line_range.is_some()
+        || options.preceded_by.is_some()
+        || options.followed_by.is_some()
+        || options.pcre
+        || options.encoding.is_some()
+    {
+        return Ok(None);
+    }
+    let file = File::open(filename).map_err(|err| SearchError::io(filename, err))?;
+    if file
+        .metadata()
+        .map_or(true, |metadata| metadata.len() < MMAP_MIN_SIZE)
+    {
+        return Ok(None);
+    }
+    // SAFETY: the file may be changed by the other processes while mapped, then the found
+    // lines may be wrong, the same as the file changed while read line by line.
+    let Ok(mapped) = (unsafe { Mmap::map(&file) }) else {
+        return Ok(None);
+    };
+    let Ok(code) = std::str::from_utf8(&mapped) else {
+        return Ok(None);
+    };
+    let invalid_regex = |source: regex::Error| SearchError::InvalidRegex {
+        path: filename.to_string(),
+        source: source.into(),
+    };
+    let matcher = options.key_regex(key).map_err(invalid_regex)?;
+    // `^` and `$` match at each line like in the line by line search.
+    let lines_matcher = options
+        .key_regex_builder(key)
+        .multi_line(true)
+        .crlf(true)
+        .build()
+        .map_err(invalid_regex)?;
+
+    let mut lines = Vec::new();
+    let mut line_number = 1;
+    // the newlines before it are counted in `line_number`.
+    let mut counted = 0;
+    let mut offset = 0;
+    while let Some(matched) = lines_matcher.find_at(code, offset) {
+        if options
+            .max_count
+            .is_some_and(|max_count| lines.len() >= max_count)
+        {
+            break;
+        }
+        let line_start = code[offset..matched.start()]
+            .rfind('\n')
+            .map_or(offset, |index| offset + index + 1);
+        let line_end = code[matched.start()..]
+            .find('\n')
+            .map_or(code.len(), |index| matched.start() + index);
+        line_number += code[counted..line_start]
+            .bytes()
+            .filter(|byte| *byte == b'\n')
+            .count();
+        counted = line_start;
+        let line = &code[line_start..line_end];
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        // the match across the lines is checked again in the line.
+        if let Some(found) = matcher.find(line) {
+            lines.push(FoundLine {
+                line: line_number,
+                col: found.start() + 1,
+                byte_offset: Some(line_start + found.start()),
+                byte_end: Some(line_start + found.end()),
+                text: line.to_string(),
+                score: None,
+                symbol: None,
+                scope: None,
+                body_lines: None,
+                key_path: None,
+                kind: None,
+                signature: None,
+                next_symbols: None,
+            });
+        }
+        offset = line_end + 1;
+        if offset > code.len() {
+            break;
+        }
+    }
+    Ok(Some(lines))
+}
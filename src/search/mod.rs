@@ -0,0 +1,2271 @@
+/*! The search engine, search the key or the symbols in the files.
+
+    The `search_code` binary is a thin CLI wrapper of this module.
+*/
+
+use colored::Colorize;
+use encoding_rs::Encoding;
+use flate2::read::GzDecoder;
+use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
+use tree_sitter::{Language, Node, Point, Query, QueryCursor, StreamingIterator, Tree};
+
+use std::{
+    collections::{btree_map, BTreeMap, HashMap, HashSet},
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime},
+};
+
+pub mod builder;
+pub mod error;
+pub mod highlight;
+pub mod index;
+pub mod matcher;
+pub mod mmap;
+pub mod replace;
+
+use error::SearchError;
+use matcher::{KeyMatcher, MatcherError};
+
+use crate::{
+    archive::{archive_files, is_archive},
+    diff::{self, AddedLines},
+    parser_lang::{decode, parse_errors, SpecifyParser},
+    Options::{
+        constants::{
+            FUNCTION_SCOPE_KINDS, LIST_SKIPPED_CAPTURES, LOCK_FILE_NAMES, TEXT_EXTENSIONS,
+        },
+        get_kind_query, get_scope_kinds, IgnoreDir, Lang, LineRange, SymbolMatch,
+    },
+};
+
+/// A found line of the search.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FoundLine {
+    /// the 1-based line number.
+    pub line: usize,
+    /// the 1-based byte column where the match starts in the line.
+    pub col: usize,
+    /// the byte offset where the match starts in the file, `None` if unknown.
+    pub byte_offset: Option<usize>,
+    /// the byte offset where the match ends in the file, exclusive, `None` if unknown.
+    /// The byte range of the match or the symbol is `byte_offset..byte_end`.
+    pub byte_end: Option<usize>,
+    /// the line contains the key or the symbol.
+    pub text: String,
+    /// the symbol found at `col` in the line, `None` in the raw text search.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// the function or the class enclosing the match, like `function parse_header`.
+    /// Only set in the raw text search with `SearchOptions::context_symbol`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// the 1-based first and last lines of the function enclosing the match.
+    /// Only set with `SearchOptions::context_function_body`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_lines: Option<(usize, usize)>,
+    /// the dotted path to the found key from the root, like `server.ports[0].name`,
+    /// or the type and the labels of the found HCL block, like `resource "aws_instance" "web"`.
+    /// Only set in the JSON and the HCL symbol search.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_path: Option<String>,
+    /// the kind of the symbol, the capture name of the query like `function` or `struct`.
+    /// Only set in the symbol search.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// the one-line signature of the function symbol, like `fn parse(input: &str) -> Tree`.
+    /// Only set in the symbol search with `SearchOptions::signatures`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// the names of the next symbols after the found line in the source order.
+    /// Only set with `SearchOptions::after_context_symbol`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_symbols: Option<Vec<String>>,
+    /// the fuzzy match score of the symbol, `None` if not the fuzzy symbol match.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<i64>,
+}
+
+/// filename -> [found line...], sorted by filename.
+pub type FindLines = BTreeMap<String, Vec<FoundLine>>;
+
+/// Search a file: (filename, key, parser, options) -> the found lines.
+pub type FileOperation =
+    fn(&str, &str, &mut SpecifyParser, &SearchOptions) -> Result<FindLines, SearchError>;
+
+/// Search a content without reading a file: (name, content, key, parser, options) -> the found lines.
+pub type ContentOperation =
+    fn(&str, &str, &str, &mut SpecifyParser, &SearchOptions) -> Result<FindLines, SearchError>;
+
+/// Get the found lines of a file as soon as it is searched: (filename, found lines).
+pub type FoundVisitor = Box<dyn Fn(&str, &[FoundLine]) + Send + Sync>;
+
+/// The count of the files searched and the lines found for a language.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LangStats {
+    pub files: usize,
+    pub matches: usize,
+}
+
+/// The options of the search, the default is to search the raw text without limits.
+#[derive(Debug, Default, Clone)]
+pub struct SearchOptions {
+    /// search the symbols with tree-sitter instead of the raw text.
+    pub is_symbol: bool,
+    /// stop reading a file after `max_count` lines found.
+    pub max_count: Option<usize>,
+    /// stop the whole search after `max_total` lines found.
+    pub max_total: Option<usize>,
+    /// the symbol names to drop from the result.
+    pub exclude_symbol: Vec<String>,
+    /// detect the language of each file from its extension, instead of using the given one.
+    pub auto: bool,
+    /// only search the files whose name matches, if set.
+    pub include: Option<GlobSet>,
+    /// never search the files whose name matches.
+    pub exclude: Option<GlobSet>,
+    /// only search the files with these extensions, if any. set by the file types.
+    pub type_extensions: Vec<&'static str>,
+    /// how the key matches the symbol names.
+    pub symbol_match: SymbolMatch,
+    /// only the lines in the range can match in the raw text search.
+    pub line_range: Option<LineRange>,
+    /// give up parsing a file after the timeout in the symbol search, `None` to never give up.
+    pub parse_timeout: Option<Duration>,
+    /// the key is a regex in the raw text search, instead of a literal text.
+    pub regex: bool,
+    /// the key only matches the whole words in the raw text search.
+    pub word: bool,
+    /// the key matches regardless of the case in the raw text search.
+    pub ignore_case: bool,
+    /// scan the symlinks to the files and the dirs, instead of skipping them.
+    pub follow_symlinks: bool,
+    /// only scan `max_depth` levels of the dirs, 1 for the files directly in the dir.
+    pub max_depth: Option<usize>,
+    /// don't draw the progress bar and the warnings.
+    pub quiet: bool,
+    /// skip the files larger than `max_filesize` bytes when walking the dir.
+    pub max_filesize: Option<u64>,
+    /// the line before the found line must match the regex in the raw text search.
+    pub preceded_by: Option<String>,
+    /// the line after the found line must match the regex in the raw text search.
+    pub followed_by: Option<String>,
+    /// decompress the `.gz` files in the raw text search.
+    pub search_compressed: bool,
+    /// the tree-sitter query to use instead of the built-in ones in the symbol search.
+    pub query: Option<String>,
+    /// only run the built-in queries capturing these symbol kinds, like `function`, all if empty.
+    /// See `Options::get_kind_query`, not used with `query`.
+    pub kinds: Vec<String>,
+    /// find the function or the class enclosing each match in the raw text search.
+    pub context_symbol: bool,
+    /// find the lines of the function enclosing each match, see `FoundLine::body_lines`.
+    pub context_function_body: bool,
+    /// find the symbols whose names do NOT match the key in the symbol search.
+    pub invert_symbol: bool,
+    /// find all the symbols in the symbol search, the key is ignored.
+    pub list_symbols: bool,
+    /// skip the files of these languages, `Lang::Any` is never skipped.
+    pub not_langs: Vec<Lang>,
+    /// skip the files last modified before the time, if set.
+    pub modified_after: Option<SystemTime>,
+    /// skip the files last modified after the time, if set.
+    pub modified_before: Option<SystemTime>,
+    /// map the large files into the memory in the raw text search, see `mmap::find_key_mapped`.
+    pub mmap: bool,
+    /// match the key with the `fancy-regex` engine in the raw text search, see `matcher`.
+    pub pcre: bool,
+    /// decode the files from the encoding, `None` for UTF-8 with the invalid bytes replaced.
+    pub encoding: Option<&'static Encoding>,
+    /// detect the language of the file of unknown extension from its shebang or modeline.
+    pub detect_modeline: bool,
+    /// search the files in the archives like `.zip` and `.tar.gz`, see `SearchCode::search_archive`.
+    pub search_archives: bool,
+    /// find the signature of each function symbol, see `FoundLine::signature`.
+    pub signatures: bool,
+    /// find the next N symbols after each found line, see `FoundLine::next_symbols`, 0 to not.
+    pub after_context_symbol: usize,
+    /// tell the files with syntax errors in the symbol search, their symbols may be incomplete.
+    pub warn_parse_errors: bool,
+    /// search every file in `Lang::Any`, instead of only the text files, see `matches_text`.
+    pub all_files: bool,
+    /// the extensions of the text files searched in `Lang::Any`, empty for `TEXT_EXTENSIONS`.
+    pub text_extensions: Vec<String>,
+    /// keep the parsed files to reuse them in the next searches, see `SpecifyParser::enable_cache`.
+    pub cache_asts: bool,
+}
+
+impl SearchOptions {
+    /** Build the regex to match the key in the raw text.
+
+    *NOTE:* the key is escaped unless `regex` is set, and wrapped by `\b` if `word` is set.
+    The case is ignored if `ignore_case` is set.
+
+    ```rust
+    use search_code::search::SearchOptions;
+
+    let options = SearchOptions {
+        word: true,
+        ..Default::default()
+    };
+    let matcher = options.key_regex("a.b").unwrap();
+    assert!(matcher.is_match("let x = a.b;"));
+    assert!(!matcher.is_match("let x = a.bc;"));
+    assert!(!matcher.is_match("let x = axb;"));
+    ```
+    */
+    pub fn key_regex(&self, key: &str) -> Result<Regex, regex::Error> {
+        self.key_regex_builder(key).build()
+    }
+
+    /// The builder of `key_regex`, to set more flags before building.
+    pub fn key_regex_builder(&self, key: &str) -> RegexBuilder {
+        let mut builder = RegexBuilder::new(&self.key_pattern(key));
+        builder.case_insensitive(self.ignore_case);
+        builder
+    }
+
+    /// Build the matcher of the key, with the `fancy-regex` engine if `pcre` is set,
+    /// or the same as `key_regex`.
+    pub fn key_matcher(&self, key: &str) -> Result<KeyMatcher, MatcherError> {
+        if !self.pcre {
+            return Ok(KeyMatcher::Regex(self.key_regex(key)?));
+        }
+        let pattern = self.key_pattern(key);
+        let pattern = if self.ignore_case {
+            format!("(?i){pattern}")
+        } else {
+            pattern
+        };
+        Ok(KeyMatcher::Pcre(fancy_regex::Regex::new(&pattern)?))
+    }
+
+    /// Build the matcher of `preceded_by` or `followed_by`, always a regex with the
+    /// `fancy-regex` engine if `pcre` is set.
+    ///
+    /// *NOTE:* `regex`, `word` and `ignore_case` are the flags of the key only, use
+    /// `(?i)` or `\b` in the pattern instead.
+    pub fn context_matcher(&self, pattern: &str) -> Result<KeyMatcher, MatcherError> {
+        if self.pcre {
+            Ok(KeyMatcher::Pcre(fancy_regex::Regex::new(pattern)?))
+        } else {
+            Ok(KeyMatcher::Regex(Regex::new(pattern)?))
+        }
+    }
+
+    /// The key escaped unless `regex` is set, and wrapped by `\b` if `word` is set.
+    fn key_pattern(&self, key: &str) -> String {
+        let pattern = if self.regex {
+            key.to_string()
+        } else {
+            regex::escape(key)
+        };
+        if self.word {
+            format!(r"\b(?:{pattern})\b")
+        } else {
+            pattern
+        }
+    }
+
+    /// Check if the file extension is one of the `type_extensions`, if any.
+    pub fn matches_type(&self, path: &Path) -> bool {
+        self.type_extensions.is_empty()
+            || path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| self.type_extensions.contains(&ext))
+    }
+
+    /** Check if the file is a text file to search in `Lang::Any`, or `all_files` is set.
+
+    *NOTE:* the file without extension is a text file, like `Makefile`. The lockfiles in
+    `LOCK_FILE_NAMES` are not, and the `.gz` files are with `search_compressed`.
+
+    ```rust
+    use std::path::Path;
+    use search_code::search::SearchOptions;
+
+    let options = SearchOptions::default();
+    assert!(options.matches_text(Path::new("./src/main.rs")));
+    assert!(options.matches_text(Path::new("./README.MD")));
+    assert!(options.matches_text(Path::new("./Makefile")));
+    assert!(!options.matches_text(Path::new("./logo.png")));
+    assert!(!options.matches_text(Path::new("./Cargo.lock")));
+    assert!(!options.matches_text(Path::new("./package-lock.json")));
+
+    let options = SearchOptions {
+        text_extensions: vec!["log".to_string()],
+        ..Default::default()
+    };
+    assert!(options.matches_text(Path::new("./app.log")));
+    assert!(!options.matches_text(Path::new("./main.rs")));
+    ```
+    */
+    pub fn matches_text(&self, path: &Path) -> bool {
+        if self.all_files {
+            return true;
+        }
+        if path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| LOCK_FILE_NAMES.contains(&name))
+        {
+            return false;
+        }
+        let Some(ext) = path.extension() else {
+            return true;
+        };
+        let ext = ext.to_string_lossy().to_lowercase();
+        if self.search_compressed && ext == "gz" {
+            return true;
+        }
+        if self.text_extensions.is_empty() {
+            TEXT_EXTENSIONS.contains(&ext.as_str())
+        } else {
+            self.text_extensions
+                .iter()
+                .any(|text| text.eq_ignore_ascii_case(&ext))
+        }
+    }
+
+    /// Check if the raw text search parses the found files, to find the symbols around the lines.
+    fn needs_ast(&self) -> bool {
+        self.context_symbol || self.context_function_body || self.after_context_symbol > 0
+    }
+
+    /// Check if the file name passes the `include` and `exclude` globs.
+    pub fn matches_glob(&self, path: &Path) -> bool {
+        let Some(filename) = path.file_name() else {
+            return false;
+        };
+        self.include
+            .as_ref()
+            .is_none_or(|include| include.is_match(filename))
+            && !self
+                .exclude
+                .as_ref()
+                .is_some_and(|exclude| exclude.is_match(filename))
+    }
+}
+
+/** Build a glob set from the patterns, like `*.rs`.
+
+*Return:* `None` if no pattern is given.
+
+```rust
+use search_code::search::build_globset;
+
+let globs = build_globset(&["*.rs".to_string()]).unwrap().unwrap();
+assert!(globs.is_match("main.rs"));
+assert!(build_globset(&[]).unwrap().is_none());
+```
+*/
+pub fn build_globset(patterns: &[String]) -> Result<Option<GlobSet>, globset::Error> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    builder.build().map(Some)
+}
+
+/** Search the key or the symbols in a file or a directory.
+
+*Use:*
+
+```no_run
+use std::path::Path;
+use search_code::{
+    search::{SearchCode, SearchOptions},
+    Options::{IgnoreDir, Lang},
+};
+
+let mut search = SearchCode::build(&Lang::Rust, IgnoreDir::new());
+search.set_options(SearchOptions {
+    is_symbol: true,
+    ..Default::default()
+});
+let found = search.search(Path::new("./src"), "main").unwrap();
+```
+*/
+pub struct SearchCode {
+    parser: SpecifyParser,
+    /// the parsers of the detected languages, used in the auto mode or for more than one language.
+    parsers: HashMap<Lang, SpecifyParser>,
+    langs: Vec<Lang>,
+    pathes: Vec<String>,
+    ignore_pathes: IgnoreDir,
+    options: SearchOptions,
+    scanned: usize,
+    /// the detected language of each searched file -> the stats.
+    stats: HashMap<Lang, LangStats>,
+    /// the canonical dirs scanned, to break the symlink cycles.
+    visited: HashSet<PathBuf>,
+    /// stream the found lines instead of keeping them in the result, see `set_visitor`.
+    visitor: Option<FoundVisitor>,
+}
+
+impl SearchCode {
+    pub fn build(lang: &Lang, ignore: IgnoreDir) -> Self {
+        Self::build_langs(std::slice::from_ref(lang), ignore)
+    }
+
+    /// Build the search of the files of any of the languages.
+    ///
+    /// *NOTE:* with more than one language, the parser of each file is picked from its
+    /// language, so the symbol search uses the right queries.
+    pub fn build_langs(langs: &[Lang], ignore: IgnoreDir) -> Self {
+        let lang = match langs {
+            [lang] => lang.clone(),
+            _ => Lang::Any,
+        };
+        Self {
+            parser: SpecifyParser::from_lang(&lang),
+            parsers: HashMap::new(),
+            langs: langs.to_vec(),
+            pathes: vec![],
+            ignore_pathes: ignore,
+            options: SearchOptions::default(),
+            scanned: 0,
+            stats: HashMap::new(),
+            visited: HashSet::new(),
+            visitor: None,
+        }
+    }
+
+    /// Set the options of the search.
+    pub fn set_options(&mut self, options: SearchOptions) {
+        self.parser.set_timeout(options.parse_timeout);
+        self.parser.set_encoding(options.encoding);
+        if options.cache_asts {
+            self.parser.enable_cache();
+        }
+        for parser in self.parsers.values_mut() {
+            parser.set_timeout(options.parse_timeout);
+            parser.set_encoding(options.encoding);
+            parser.share_cache(&self.parser);
+        }
+        self.options = options;
+    }
+
+    /** Stream the found lines of each file to the visitor as soon as the file is searched.
+
+    *NOTE:* the streamed lines are not kept, so the searches return the empty result.
+    The walked files are searched one by one then, so they are visited in the path order
+    of `scan_dir`. `options.max_total` still limits the lines streamed in a walk.
+
+    ```rust
+    use std::sync::{Arc, Mutex};
+    use search_code::{search::SearchCode, Options::{IgnoreDir, Lang}};
+
+    let dir = tempfile::tempdir().unwrap();
+    for file in ["b.txt", "a.txt", "c.txt"] {
+        std::fs::write(dir.path().join(file), "key\nno\nkey\n").unwrap();
+    }
+
+    let streamed = Arc::new(Mutex::new(Vec::new()));
+    let mut search = SearchCode::build(&Lang::Any, IgnoreDir::new());
+    let sink = Arc::clone(&streamed);
+    search.set_visitor(Box::new(move |filename, lines| {
+        sink.lock().unwrap().push((filename.to_string(), lines.len()));
+    }));
+    let found = search.search(dir.path(), "key").unwrap();
+    assert!(found.is_empty());
+    let expected: Vec<_> = ["a.txt", "b.txt", "c.txt"]
+        .iter()
+        .map(|file| (dir.path().join(file).to_str().unwrap().to_string(), 2))
+        .collect();
+    assert_eq!(*streamed.lock().unwrap(), expected);
+    ```
+    */
+    pub fn set_visitor(&mut self, visitor: FoundVisitor) {
+        self.visitor = Some(visitor);
+    }
+
+    /// Stream the found lines to the visitor if set, see `set_visitor`.
+    ///
+    /// *Return:* the found lines not streamed, all of them if no visitor is set.
+    fn stream(&self, result: FindLines) -> FindLines {
+        match &self.visitor {
+            Some(visitor) => {
+                visit_found(visitor, result, None);
+                BTreeMap::new()
+            }
+            None => result,
+        }
+    }
+
+    /// Search the key in the path, the path can be a file or a directory.
+    ///
+    /// *NOTE:* search the symbols if `options.is_symbol` is set, or the raw text.
+    /// In the auto mode, the files of unknown language fall back to the raw text search.
+    pub fn search(&mut self, path: &Path, key: &str) -> Result<FindLines, SearchError> {
+        let op = self.operating();
+        if path.is_dir() {
+            self.walk_dir(path, key, &op)
+        } else if self.options.search_archives && is_archive(path) {
+            self.search_archive(path_str(path)?, key)
+        } else {
+            let path = path_str(path)?;
+            self.scanned += 1;
+            let parser = select_parser(
+                &mut self.parser,
+                &mut self.parsers,
+                &self.langs,
+                &self.options,
+                path,
+            );
+            let found = op(path, key, parser, &self.options)?;
+            warn_file_parse_errors(path, parser, &self.options, &ProgressBar::hidden());
+            record_stats(
+                &mut self.stats,
+                path,
+                found.values().map(Vec::len).sum(),
+                self.options.detect_modeline,
+            );
+            Ok(self.stream(found))
+        }
+    }
+
+    /// Search the key in each path, the paths can be files or directories.
+    ///
+    /// *NOTE:* the results of all the paths are merged, a file found by more than one
+    /// path is kept once. `options.max_total` limits the lines found in all the paths.
+    pub fn search_all(&mut self, paths: &[&Path], key: &str) -> Result<FindLines, SearchError> {
+        // the dirs are visited again in each run of the watch mode.
+        self.visited.clear();
+        let mut found = BTreeMap::new();
+        let mut total = 0;
+        for path in paths {
+            for (filename, lines) in self.search(path, key)? {
+                if let btree_map::Entry::Vacant(entry) = found.entry(filename) {
+                    total += lines.len();
+                    entry.insert(lines);
+                }
+            }
+            if self
+                .options
+                .max_total
+                .is_some_and(|max_total| total >= max_total)
+            {
+                break;
+            }
+        }
+        if let Some(max_total) = self.options.max_total {
+            truncate_total(&mut found, max_total);
+        }
+        Ok(found)
+    }
+
+    /// Search the key in the lines added in the git staging area.
+    ///
+    /// *NOTE:* the line number is the line number in the staged file.
+    pub fn search_staged(&mut self, key: &str) -> Result<FindLines, SearchError> {
+        let command = "git diff --cached";
+        let staged = diff::staged_added_lines().map_err(|err| SearchError::io(command, err))?;
+        self.search_added(staged, key, |filename| {
+            diff::staged_content(filename).map_err(|err| SearchError::io(filename, err))
+        })
+    }
+
+    /// Search the key in the lines added or modified in the working tree since the git ref,
+    /// like `main`, only the files under the paths are diffed.
+    ///
+    /// *NOTE:* the line number is the line number in the working tree file,
+    /// the filenames are relative to the current directory, see `diff::ref_added_lines`.
+    pub fn search_diff(
+        &mut self,
+        paths: &[&Path],
+        key: &str,
+        rev: &str,
+    ) -> Result<FindLines, SearchError> {
+        let command = format!("git diff {rev}");
+        let paths: Vec<&str> = paths.iter().filter_map(|path| path.to_str()).collect();
+        let added =
+            diff::ref_added_lines(rev, &paths).map_err(|err| SearchError::io(&command, err))?;
+        self.search_added(added, key, |filename| {
+            std::fs::read(filename).map_err(|err| SearchError::io(filename, err))
+        })
+    }
+
+    /// Search the key in the added lines of a git diff, `read` reads the new file.
+    ///
+    /// *NOTE:* the whole new file is searched with the options, like the symbols,
+    /// `line_range` or `preceded_by`, then only the lines found in the added lines are kept,
+    /// and `max_count` of them.
+    fn search_added(
+        &mut self,
+        added: AddedLines,
+        key: &str,
+        read: impl Fn(&str) -> Result<Vec<u8>, SearchError>,
+    ) -> Result<FindLines, SearchError> {
+        let op = self.operating_content();
+        // the found lines out of the added lines don't count.
+        let options = SearchOptions {
+            max_count: None,
+            ..self.options.clone()
+        };
+        let mut found = BTreeMap::new();
+        for (filename, lines) in added {
+            if !self.should_search(Path::new(&filename)) {
+                continue;
+            }
+            self.scanned += 1;
+            let added: HashSet<usize> = lines.iter().map(|(line, _)| *line).collect();
+            let content = decode(read(&filename)?, options.encoding);
+            let parser = select_parser(
+                &mut self.parser,
+                &mut self.parsers,
+                &self.langs,
+                &options,
+                &filename,
+            );
+            let mut lines: Vec<_> = op(&filename, &content, key, parser, &options)?
+                .remove(&filename)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|found| added.contains(&found.line))
+                .collect();
+            if let Some(max_count) = self.options.max_count {
+                lines.truncate(max_count);
+            }
+            record_stats(
+                &mut self.stats,
+                &filename,
+                lines.len(),
+                self.options.detect_modeline,
+            );
+            if !lines.is_empty() {
+                found.extend(self.stream(BTreeMap::from([(filename, lines)])));
+            }
+        }
+
+        Ok(found)
+    }
+
+    /// Search the key in the files under the paths at the git revision, like `HEAD~3`.
+    ///
+    /// *NOTE:* the files are read from git, not the working tree, and named like
+    /// `HEAD~3:src/main.rs`. The ignored dirs are not checked, git has no `.git` in the tree.
+    pub fn search_rev(
+        &mut self,
+        paths: &[&Path],
+        key: &str,
+        rev: &str,
+    ) -> Result<FindLines, SearchError> {
+        let command = format!("git revision {rev}");
+        let paths: Vec<&str> = paths.iter().filter_map(|path| path.to_str()).collect();
+        let files = diff::rev_files(rev, &paths).map_err(|err| SearchError::io(&command, err))?;
+        let mut found = BTreeMap::new();
+        let mut total = 0;
+        for (filename, content) in files {
+            if !self.should_search(Path::new(&filename)) {
+                continue;
+            }
+            let lines =
+                self.search_content(&filename, &format!("{rev}:{filename}"), &content, key)?;
+            total += lines.values().map(Vec::len).sum::<usize>();
+            found.extend(lines);
+            if self
+                .options
+                .max_total
+                .is_some_and(|max_total| total >= max_total)
+            {
+                break;
+            }
+        }
+        if let Some(max_total) = self.options.max_total {
+            truncate_total(&mut found, max_total);
+        }
+        Ok(found)
+    }
+
+    /** Search the key in the content, without reading the file.
+
+    *PARAM:* {path} The path to detect the language from, and to count in the stats.
+
+    *PARAM:* {name} The name of the content in the result, like `HEAD~3:src/main.rs`.
+
+    *NOTE:* the content is searched even if the path doesn't pass the filters.
+
+    ```rust
+    use search_code::{
+        search::{SearchCode, SearchOptions},
+        Options::{IgnoreDir, Lang},
+    };
+
+    let mut search = SearchCode::build(&Lang::Rust, IgnoreDir::new());
+    search.set_options(SearchOptions {
+        is_symbol: true,
+        ..Default::default()
+    });
+    let found = search
+        .search_content("a.rs", "<stdin>", "fn main() {}\nfn other() {}\n", "other")
+        .unwrap();
+    assert_eq!(found["<stdin>"][0].line, 2);
+    assert_eq!(search.scanned(), 1);
+    ```
+    */
+    pub fn search_content(
+        &mut self,
+        path: &str,
+        name: &str,
+        content: &str,
+        key: &str,
+    ) -> Result<FindLines, SearchError> {
+        let op = self.operating_content();
+        self.scanned += 1;
+        let parser = select_parser(
+            &mut self.parser,
+            &mut self.parsers,
+            &self.langs,
+            &self.options,
+            path,
+        );
+        let found = op(name, content, key, parser, &self.options)?;
+        record_stats(
+            &mut self.stats,
+            path,
+            found.values().map(Vec::len).sum(),
+            self.options.detect_modeline,
+        );
+        Ok(self.stream(found))
+    }
+
+    /// The number of the files searched so far.
+    pub fn scanned(&self) -> usize {
+        self.scanned
+    }
+
+    /// The files searched and the lines found so far, by the language detected from
+    /// the file extension.
+    ///
+    /// *NOTE:* the files of unknown language are counted as `Lang::Any`.
+    pub fn stats(&self) -> &HashMap<Lang, LangStats> {
+        &self.stats
+    }
+
+    /// Set the files to search, instead of scanning a directory.
+    pub fn set_pathes(&mut self, pathes: Vec<String>) {
+        self.pathes = pathes;
+    }
+
+    /// Search the key in the files set by `set_pathes`.
+    ///
+    /// *NOTE:* the files not matching the language are still skipped.
+    pub fn search_pathes(&mut self, key: &str) -> Result<FindLines, SearchError> {
+        let op = self.operating();
+        let archives = self.take_archives();
+        let mut found = self.walk_pathes(key, &op)?;
+        for archive in archives {
+            found.extend(self.search_archive(&archive, key)?);
+        }
+        Ok(found)
+    }
+
+    /** Search the key in the files of the archive, named like `dist.zip:src/main.rs`.
+
+    *NOTE:* the files in the archive pass the language and the file filters like the others,
+    the binary and the non-UTF-8 files are skipped, see `archive::archive_files`.
+    */
+    pub fn search_archive(&mut self, path: &str, key: &str) -> Result<FindLines, SearchError> {
+        let files = archive_files(Path::new(path)).map_err(|err| SearchError::io(path, err))?;
+        let mut found = BTreeMap::new();
+        for (filename, content) in files {
+            if !self.should_search(Path::new(&filename)) {
+                continue;
+            }
+            found.extend(self.search_content(
+                &filename,
+                &format!("{path}:{filename}"),
+                &content,
+                key,
+            )?);
+        }
+        Ok(found)
+    }
+
+    /// Take the archives out of `self.pathes` with `options.search_archives`,
+    /// they are searched by `search_archive` instead.
+    fn take_archives(&mut self) -> Vec<String> {
+        if !self.options.search_archives {
+            return Vec::new();
+        }
+        let (archives, pathes) = std::mem::take(&mut self.pathes)
+            .into_iter()
+            .partition(|path| is_archive(Path::new(path)));
+        self.pathes = pathes;
+        archives
+    }
+
+    /// Scan the path and get the files that would be searched, without searching.
+    pub fn list_files(&mut self, path: &Path) -> Result<Vec<String>, SearchError> {
+        self.pathes = self.scan_dir(path)?;
+        Ok(self.filtered_pathes())
+    }
+
+    /// Get the files in `self.pathes` that pass the language and the file filters.
+    pub fn filtered_pathes(&self) -> Vec<String> {
+        self.pathes
+            .iter()
+            .filter(|path| self.should_search(Path::new(path)))
+            .cloned()
+            .collect()
+    }
+
+    /// Check if the file passes the language and the file filters.
+    ///
+    /// *NOTE:* the language is not checked in the auto mode,
+    /// or the file matches if any of the languages matches it.
+    /// Only the text files are searched in `Lang::Any`, see `SearchOptions::matches_text`.
+    fn should_search(&self, path: &Path) -> bool {
+        self.matches_lang(path)
+            && (!self.langs.contains(&Lang::Any) || self.options.matches_text(path))
+            && self.options.matches_glob(path)
+            && self.options.matches_type(path)
+    }
+
+    /// Check if the changed file would be searched, used by the watch mode.
+    ///
+    /// *NOTE:* the file in an ignored dir is not searched, like the `target` dir.
+    pub fn is_watched(&self, path: &Path) -> bool {
+        let ignored = path.ancestors().any(|dir| {
+            dir.to_str()
+                .is_some_and(|dir| self.ignore_pathes.is_ignore(dir))
+                || dir
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| self.ignore_pathes.is_ignore(&format!("./{name}")))
+        });
+        !ignored && self.should_search(path)
+    }
+
+    /// Check if any of the languages matches the file, and none of `options.not_langs`.
+    ///
+    /// *NOTE:* all the languages match in the auto mode.
+    fn matches_lang(&self, path: &Path) -> bool {
+        let modeline = self.options.detect_modeline;
+        (self.options.auto
+            || self
+                .langs
+                .iter()
+                .any(|lang| lang.matches_file(path, modeline)))
+            && !self
+                .options
+                .not_langs
+                .iter()
+                .any(|lang| *lang != Lang::Any && lang.matches_file(path, modeline))
+    }
+
+    /// Get the operation to do for each file according to the options.
+    fn operating(&self) -> FileOperation {
+        match (self.options.is_symbol, self.options.auto) {
+            (true, true) => get_symbols_or_key,
+            (true, false) => get_symbols,
+            (false, _) => find_key_file,
+        }
+    }
+
+    /// Get the operation to do for each content according to the options, see `operating`.
+    fn operating_content(&self) -> ContentOperation {
+        match (self.options.is_symbol, self.options.auto) {
+            (true, true) => get_symbols_or_key_content,
+            (true, false) => get_symbols_content,
+            (false, _) => find_key_content,
+        }
+    }
+
+    /// Walk through the directory and do the operation.
+    ///
+    /// *Return:* : The vector of the found that contains key.
+    /// - The key is the filename.
+    /// - The value is the found lines with their positions.
+    pub fn walk_dir<Operating>(
+        &mut self,
+        dirname: &Path,
+        key: &str,
+        op: &Operating,
+    ) -> Result<FindLines, SearchError>
+    where
+        Operating: Fn(&str, &str, &mut SpecifyParser, &SearchOptions) -> Result<FindLines, SearchError>
+            + Sync,
+    {
+        self.pathes = self.scan_dir(dirname)?;
+        let archives = self.take_archives();
+        let mut found = self.walk_pathes(key, op)?;
+        for archive in archives {
+            found.extend(self.search_archive(&archive, key)?);
+        }
+        Ok(found)
+    }
+
+    /// Do the operation for each file in `self.pathes`.
+    ///
+    /// *NOTE:* the files are searched in parallel on the rayon thread pool,
+    /// or one by one if the pool has only one thread, like `--threads 1`, or to stream
+    /// the found lines to the visitor, see `set_visitor`.
+    pub fn walk_pathes<Operating>(
+        &mut self,
+        key: &str,
+        op: &Operating,
+    ) -> Result<FindLines, SearchError>
+    where
+        Operating: Fn(&str, &str, &mut SpecifyParser, &SearchOptions) -> Result<FindLines, SearchError>
+            + Sync,
+    {
+        let mut found = BTreeMap::new();
+        let mut total = 0;
+        let progress_bar = if self.options.quiet {
+            ProgressBar::hidden()
+        } else {
+            ProgressBar::new(self.pathes.len() as u64)
+        };
+        progress_bar.set_style(
+            ProgressStyle::with_template("[{elapsed}] {bar:40.cyan/blue} [{pos:>5}/{len:5}] {msg}")
+                .unwrap()
+                .progress_chars("##-"),
+        );
+        progress_bar.set_message(format!("Searching \"{key}\" ...").bright_blue().to_string());
+        progress_bar.enable_steady_tick(Duration::from_millis(100));
+        // the visitor gets the files in the path order, one by one.
+        if rayon::current_num_threads() > 1 && self.visitor.is_none() {
+            return self.walk_pathes_parallel(key, op, &progress_bar);
+        }
+
+        for path in &self.pathes {
+            progress_bar.inc(1);
+            if !self.should_search(Path::new(path)) {
+                continue;
+            }
+            if is_too_large(path, &self.options, &progress_bar)?
+                || !is_modified_within(path, &self.options)?
+            {
+                continue;
+            }
+            self.scanned += 1;
+            let parser = select_parser(
+                &mut self.parser,
+                &mut self.parsers,
+                &self.langs,
+                &self.options,
+                path,
+            );
+            let result = search_walked(op, path, key, parser, &self.options, &progress_bar)?;
+            let mut matches = 0;
+            let mut kept = BTreeMap::new();
+            for (filename, mut lines) in result {
+                if let Some(max_total) = self.options.max_total {
+                    lines.truncate(max_total - total);
+                }
+                total += lines.len();
+                matches += lines.len();
+                if !lines.is_empty() {
+                    kept.insert(filename, lines);
+                }
+            }
+            found.extend(self.stream(kept));
+            record_stats(&mut self.stats, path, matches, self.options.detect_modeline);
+            if self
+                .options
+                .max_total
+                .is_some_and(|max_total| total >= max_total)
+            {
+                progress_bar.finish_with_message(
+                    format!("Stopped after reaching the max total {total} lines")
+                        .yellow()
+                        .to_string(),
+                );
+                return Ok(found);
+            }
+        }
+        progress_bar.finish();
+
+        Ok(found)
+    }
+
+    /// Do the operation for each file in `self.pathes` in parallel, see `walk_pathes`.
+    ///
+    /// *NOTE:* each task has its own parsers, sharing the cache of the parsed files
+    /// with `self.parser`, so the cache outlives the walk. The files already started when
+    /// `options.max_total` is reached are still searched, then the result is truncated.
+    fn walk_pathes_parallel<Operating>(
+        &mut self,
+        key: &str,
+        op: &Operating,
+        progress_bar: &ProgressBar,
+    ) -> Result<FindLines, SearchError>
+    where
+        Operating: Fn(&str, &str, &mut SpecifyParser, &SearchOptions) -> Result<FindLines, SearchError>
+            + Sync,
+    {
+        let pathes: Vec<&String> = self
+            .pathes
+            .iter()
+            .filter(|path| self.should_search(Path::new(path)))
+            .collect();
+        progress_bar.set_length(pathes.len() as u64);
+        let (base, langs, options, visitor) =
+            (&self.parser, &self.langs, &self.options, &self.visitor);
+        let total = AtomicUsize::new(0);
+        let searched = pathes
+            .into_par_iter()
+            .map_init(
+                || {
+                    let mut parser = SpecifyParser::from_lang(base.get_lang());
+                    parser.set_timeout(options.parse_timeout);
+                    parser.set_encoding(options.encoding);
+                    parser.share_cache(base);
+                    (parser, HashMap::new())
+                },
+                |(parser, parsers), path| {
+                    progress_bar.inc(1);
+                    let reached = options
+                        .max_total
+                        .is_some_and(|max_total| total.load(Ordering::Relaxed) >= max_total);
+                    if reached
+                        || is_too_large(path, options, progress_bar)?
+                        || !is_modified_within(path, options)?
+                    {
+                        return Ok(None);
+                    }
+                    let parser = select_parser(parser, parsers, langs, options, path);
+                    let result = search_walked(op, path, key, parser, options, progress_bar)?;
+                    let matches: usize = result.values().map(Vec::len).sum();
+                    let before = total.fetch_add(matches, Ordering::Relaxed);
+                    let result = match visitor {
+                        Some(visitor) => {
+                            let left = options.max_total.map(|max| max.saturating_sub(before));
+                            visit_found(visitor, result, left);
+                            BTreeMap::new()
+                        }
+                        None => result,
+                    };
+                    Ok(Some((path, matches, result)))
+                },
+            )
+            .collect::<Result<Vec<_>, SearchError>>()?;
+
+        let mut found = BTreeMap::new();
+        for (path, matches, result) in searched.into_iter().flatten() {
+            self.scanned += 1;
+            record_stats(&mut self.stats, path, matches, self.options.detect_modeline);
+            found.extend(result);
+        }
+        if let Some(max_total) = self.options.max_total {
+            truncate_total(&mut found, max_total);
+        }
+        progress_bar.finish();
+        Ok(found)
+    }
+
+    /** Scan the directory and return the vector of the files' path.
+
+    *NOTE:* the ignored dirs and files are skipped, but the language and the file
+    filters are not checked, see `filtered_pathes`. A file path is returned as is.
+    The entries of each dir are in the path order, so the files are too.
+
+    *NOTE:* The symlinks are skipped unless `options.follow_symlinks` is set, then each dir
+    is scanned only once by its canonical path, so a symlink loop ends.
+
+    *NOTE:* only `options.max_depth` levels of the dirs are scanned if set.
+
+    *NOTE:* the files and the dirs whose path is not valid UTF-8 are skipped with a warning,
+    but the scanned dir itself must be valid UTF-8, or it's an error.
+
+    The ignore rules:
+
+    ```rust
+    use search_code::{search::SearchCode, Options::{IgnoreDir, Lang}};
+
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path();
+    std::fs::create_dir_all(dir.join("src/nested")).unwrap();
+    std::fs::create_dir_all(dir.join("target")).unwrap();
+    for file in ["src/main.rs", "src/nested/lib.rs", "target/out.rs", "README", "secret.rs"] {
+        std::fs::write(dir.join(file), "").unwrap();
+    }
+
+    // an ignored dir and an ignored file, the trailing `/` is normalized.
+    let mut ignore = IgnoreDir::new();
+    let target = format!("{}/", dir.join("target").to_str().unwrap());
+    let secret = dir.join("secret.rs").to_str().unwrap().to_string();
+    ignore.set_ignores(vec![target, secret]);
+    ignore.relative_dir_fix();
+    let mut search = SearchCode::build(&Lang::Rust, ignore);
+    let mut files = search.scan_dir(dir).unwrap();
+    files.sort();
+    let expected: Vec<String> = ["README", "src/main.rs", "src/nested/lib.rs"]
+        .iter()
+        .map(|file| dir.join(file).to_str().unwrap().to_string())
+        .collect();
+    assert_eq!(files, expected);
+    ```
+
+    The depth limits:
+
+    ```rust
+    use search_code::{search::{SearchCode, SearchOptions}, Options::{IgnoreDir, Lang}};
+
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path();
+    std::fs::create_dir_all(dir.join("a/b")).unwrap();
+    for file in ["top.rs", "a/one.rs", "a/b/two.rs"] {
+        std::fs::write(dir.join(file), "").unwrap();
+    }
+    let scan = |max_depth| {
+        let mut search = SearchCode::build(&Lang::Rust, IgnoreDir::new());
+        search.set_options(SearchOptions {
+            max_depth,
+            ..Default::default()
+        });
+        let mut files: Vec<String> = search
+            .scan_dir(dir)
+            .unwrap()
+            .iter()
+            .map(|file| file[dir.to_str().unwrap().len() + 1..].replace('\\', "/"))
+            .collect();
+        files.sort();
+        files
+    };
+    assert!(scan(Some(0)).is_empty());
+    assert_eq!(scan(Some(1)), ["top.rs"]);
+    assert_eq!(scan(Some(2)), ["a/one.rs", "top.rs"]);
+    assert_eq!(scan(None), ["a/b/two.rs", "a/one.rs", "top.rs"]);
+    ```
+
+    The symlinks, skipped by default, and a symlink loop followed only once:
+
+    ```rust
+    use search_code::{search::{SearchCode, SearchOptions}, Options::{IgnoreDir, Lang}};
+
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path();
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("src/main.rs"), "").unwrap();
+    // the symlink to its parent dir is a loop.
+    #[cfg(unix)]
+    let linked = std::os::unix::fs::symlink(dir.join("src"), dir.join("src/loop")).is_ok();
+    // creating a symlink may need the privilege on Windows.
+    #[cfg(windows)]
+    let linked = std::os::windows::fs::symlink_dir(dir.join("src"), dir.join("src/loop")).is_ok();
+    #[cfg(not(any(unix, windows)))]
+    let linked = false;
+
+    if linked {
+        let main = dir.join("src/main.rs").to_str().unwrap().to_string();
+        let mut search = SearchCode::build(&Lang::Rust, IgnoreDir::new());
+        assert_eq!(search.scan_dir(dir).unwrap(), [main.clone()]);
+
+        let mut search = SearchCode::build(&Lang::Rust, IgnoreDir::new());
+        search.set_options(SearchOptions {
+            follow_symlinks: true,
+            ..Default::default()
+        });
+        assert_eq!(search.scan_dir(dir).unwrap(), [main]);
+    }
+    ```
+
+    The file whose name is not valid UTF-8, where the file system allows it:
+
+    ```rust
+    use search_code::{search::SearchCode, Options::{IgnoreDir, Lang}};
+
+    let dir = tempfile::tempdir().unwrap();
+    let dir = dir.path();
+    std::fs::write(dir.join("main.rs"), "").unwrap();
+    #[cfg(unix)]
+    let created = {
+        use std::os::unix::ffi::OsStrExt;
+        let name = std::ffi::OsStr::from_bytes(b"bad\xff.rs");
+        std::fs::write(dir.join(name), "").is_ok()
+    };
+    #[cfg(not(unix))]
+    let created = false;
+
+    if created {
+        let main = dir.join("main.rs").to_str().unwrap().to_string();
+        let mut search = SearchCode::build(&Lang::Rust, IgnoreDir::new());
+        assert_eq!(search.scan_dir(dir).unwrap(), [main]);
+    }
+    ```
+    */
+    pub fn scan_dir(&mut self, dirname: &Path) -> Result<Vec<String>, SearchError> {
+        let mut files = Vec::new();
+        self.scan_dir_into(dirname, 0, &mut files)?;
+        Ok(files)
+    }
+
+    /// Scan the directory `depth` levels below the scanned path and push the files' path,
+    /// see `scan_dir`.
+    fn scan_dir_into(
+        &mut self,
+        dirname: &Path,
+        depth: usize,
+        files: &mut Vec<String>,
+    ) -> Result<(), SearchError> {
+        let dirname_str = path_str(dirname)?;
+        if !self.ignore_pathes.is_ignore(dirname_str) {
+            if dirname.is_dir() {
+                if self
+                    .options
+                    .max_depth
+                    .is_some_and(|max_depth| depth >= max_depth)
+                {
+                    return Ok(());
+                }
+                if self.options.follow_symlinks {
+                    let canonical = std::fs::canonicalize(dirname)
+                        .map_err(|err| SearchError::io(dirname_str, err))?;
+                    if !self.visited.insert(canonical) {
+                        return Ok(());
+                    }
+                }
+                let mut entries = std::fs::read_dir(dirname)
+                    .and_then(|entries| {
+                        entries
+                            .map(|entry| entry.map(|entry| entry.path()))
+                            .collect::<Result<Vec<_>, _>>()
+                    })
+                    .map_err(|err| SearchError::io(dirname_str, err))?;
+                // the files are in the path order, not in the order of the file system.
+                entries.sort();
+                for path in entries {
+                    let Some(path_str) = path.to_str() else {
+                        if !self.options.quiet {
+                            eprintln!(
+                                "{}",
+                                format!("Skipped {}: the path is not valid UTF-8", path.display())
+                                    .yellow()
+                            );
+                        }
+                        continue;
+                    };
+                    if self.ignore_pathes.is_ignore(path_str) {
+                        continue;
+                    }
+                    let is_symlink = path
+                        .symlink_metadata()
+                        .is_ok_and(|metadata| metadata.file_type().is_symlink());
+                    // the broken symlinks are skipped too.
+                    if is_symlink && (!self.options.follow_symlinks || !path.exists()) {
+                        continue;
+                    }
+                    if path.is_dir() {
+                        self.scan_dir_into(&path, depth + 1, files)?;
+                    } else {
+                        files.push(path_str.to_string());
+                    }
+                }
+            } else {
+                files.push(dirname_str.to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The path as a `&str`, or the error if it's not valid UTF-8.
+fn path_str(path: &Path) -> Result<&str, SearchError> {
+    path.to_str().ok_or_else(|| SearchError::invalid_path(path))
+}
+
+/// Check if the file is over `options.max_filesize`, and warn it's skipped.
+fn is_too_large(
+    path: &str,
+    options: &SearchOptions,
+    progress_bar: &ProgressBar,
+) -> Result<bool, SearchError> {
+    let Some(max_filesize) = options.max_filesize else {
+        return Ok(false);
+    };
+    let size = std::fs::metadata(path)
+        .map_err(|err| SearchError::io(path, err))?
+        .len();
+    if size <= max_filesize {
+        return Ok(false);
+    }
+    if !options.quiet {
+        progress_bar.suspend(|| {
+            eprintln!(
+                "{}",
+                format!("Skipped {path}: {size} bytes is over the max filesize").yellow()
+            )
+        });
+    }
+    Ok(true)
+}
+
+/// Do the operation for a walked file, see `walk_pathes` and `skip_unparsed`.
+///
+/// *NOTE:* the parse errors are warned after the symbols are searched, see `warn_file_parse_errors`.
+fn search_walked<Operating>(
+    op: &Operating,
+    path: &str,
+    key: &str,
+    parser: &mut SpecifyParser,
+    options: &SearchOptions,
+    progress_bar: &ProgressBar,
+) -> Result<FindLines, SearchError>
+where
+    Operating: Fn(&str, &str, &mut SpecifyParser, &SearchOptions) -> Result<FindLines, SearchError>,
+{
+    let result = op(path, key, parser, options);
+    if result.is_ok() {
+        warn_file_parse_errors(path, parser, options, progress_bar);
+    }
+    skip_unparsed(result, options, progress_bar)
+}
+
+/// The found lines of a walked file, or none if the file can't be parsed, like the parse timed out.
+///
+/// *NOTE:* one file that can't be parsed doesn't stop the walk, it's skipped
+/// with a warning unless `options.quiet`.
+fn skip_unparsed(
+    result: Result<FindLines, SearchError>,
+    options: &SearchOptions,
+    progress_bar: &ProgressBar,
+) -> Result<FindLines, SearchError> {
+    match result {
+        Err(SearchError::Parse { path }) => {
+            if !options.quiet {
+                progress_bar.suspend(|| {
+                    eprintln!(
+                        "{}",
+                        format!("Skipped {path}: can't parse it, see --parse-timeout").yellow()
+                    )
+                });
+            }
+            Ok(BTreeMap::new())
+        }
+        result => result,
+    }
+}
+
+/// Check if the file was last modified between `options.modified_after` and `options.modified_before`.
+fn is_modified_within(path: &str, options: &SearchOptions) -> Result<bool, SearchError> {
+    if options.modified_after.is_none() && options.modified_before.is_none() {
+        return Ok(true);
+    }
+    let modified = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|err| SearchError::io(path, err))?;
+    Ok(options.modified_after.is_none_or(|after| modified >= after)
+        && options
+            .modified_before
+            .is_none_or(|before| modified <= before))
+}
+
+/// Check if the file is gzipped by its extension.
+fn is_gzip(filename: &str) -> bool {
+    Path::new(filename)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"))
+}
+
+/// Pass the found lines of each file to the visitor, at most `limit` lines in all.
+fn visit_found(visitor: &FoundVisitor, result: FindLines, limit: Option<usize>) {
+    let mut left = limit.unwrap_or(usize::MAX);
+    for (filename, mut lines) in result {
+        lines.truncate(left);
+        left -= lines.len();
+        if !lines.is_empty() {
+            visitor(&filename, &lines);
+        }
+    }
+}
+
+/// Drop the found lines after the first `max_total` ones, in the order of the filenames.
+pub fn truncate_total(found: &mut FindLines, max_total: usize) {
+    let mut total = 0;
+    found.retain(|_, lines| {
+        lines.truncate(max_total - total);
+        total += lines.len();
+        !lines.is_empty()
+    });
+}
+
+/// Count the searched file and its found lines for the language of the file.
+fn record_stats(stats: &mut HashMap<Lang, LangStats>, path: &str, matches: usize, modeline: bool) {
+    let lang = Lang::detect(Path::new(path), modeline).unwrap_or_default();
+    let stats = stats.entry(lang).or_default();
+    stats.files += 1;
+    stats.matches += matches;
+}
+
+/// Get the parser of the file's language in the auto mode or for more than one language,
+/// or the given parser.
+///
+/// *NOTE:* the language is detected too if `options.context_symbol`
+/// or `options.context_function_body` is set without language.
+fn select_parser<'a>(
+    parser: &'a mut SpecifyParser,
+    parsers: &'a mut HashMap<Lang, SpecifyParser>,
+    langs: &[Lang],
+    options: &SearchOptions,
+    path: &str,
+) -> &'a mut SpecifyParser {
+    // the raw text search needs the parser of each file to find the scopes.
+    let detect = options.auto
+        || langs.len() > 1
+        || ((options.context_symbol || options.context_function_body)
+            && *parser.get_lang() == Lang::Any);
+    if !detect {
+        return parser;
+    }
+    let lang = Lang::detect(Path::new(path), options.detect_modeline).unwrap_or_default();
+    parsers.entry(lang.clone()).or_insert_with(|| {
+        let mut detected = SpecifyParser::from_lang(&lang);
+        detected.set_timeout(options.parse_timeout);
+        detected.set_encoding(options.encoding);
+        detected.share_cache(parser);
+        detected
+    })
+}
+
+/// This is used when just search not support language.
+///
+/// **Supported languages are:**
+/// - Cpp
+/// - Python
+/// - Rust
+/// - Java
+/// - CSharp
+/// - Bash
+/// - Kotlin
+/// - Swift
+/// - Lua
+/// - Markdown
+/// - Hcl
+/// - Json
+/// - C: treat as Cpp
+///
+/// *NOTE:* stop reading the file after `options.max_count` lines found,
+/// or after the end of `options.line_range`.
+/// The lines out of the range are skipped before matching, so only the matches
+/// are limited, the context printed around a match may still go beyond the range.
+///
+/// *NOTE:* the line numbers are 1-based, the same as `get_symbols`.
+///
+/// *NOTE:* the `.gz` file is decompressed if `options.search_compressed` is set,
+/// then the line numbers and the byte offsets are in the decompressed content.
+///
+/// *NOTE:* with `options.preceded_by` or `options.followed_by`, a line is found only
+/// if the line before or after it matches the pattern too. The patterns are always
+/// regexes, see `SearchOptions::context_matcher`.
+///
+/// ```rust
+/// use search_code::{
+///     parser_lang::SpecifyParser,
+///     search::{find_key_file, get_symbols, SearchOptions},
+///     Options::Lang,
+/// };
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("line_number.rs");
+/// std::fs::write(&path, "// the first line\r\n\nfn target() {}\n").unwrap();
+/// let path = path.to_str().unwrap();
+/// let options = SearchOptions::default();
+///
+/// let raw = find_key_file(path, "fn target", &mut SpecifyParser::new(), &options).unwrap();
+/// let mut parser = SpecifyParser::from_lang(&Lang::Rust);
+/// let symbols = get_symbols(path, "target", &mut parser, &options).unwrap();
+/// assert_eq!(raw[path][0].line, 3);
+/// assert_eq!(symbols[path][0].line, 3);
+/// assert_eq!(raw[path][0].byte_offset, Some(20));
+/// assert_eq!(symbols[path][0].byte_offset, Some(23));
+/// assert_eq!(raw[path][0].byte_end, Some(29));
+/// assert_eq!(symbols[path][0].byte_end, Some(29));
+/// ```
+///
+/// The patterns around the key are regexes, even if the key is a literal text:
+///
+/// ```rust
+/// use search_code::{
+///     parser_lang::SpecifyParser,
+///     search::{find_key_file, SearchOptions},
+/// };
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let path = dir.path().join("config.yml");
+/// std::fs::write(&path, "y: 1\nx.y\n  x.y\nx.y\nY: 2\n").unwrap();
+/// let path = path.to_str().unwrap();
+/// let options = SearchOptions {
+///     ignore_case: true,
+///     preceded_by: Some("^y".to_string()),
+///     followed_by: Some(r"^\s".to_string()),
+///     ..Default::default()
+/// };
+///
+/// // the `.` of the key is literal, `Y: 2` doesn't match the case sensitive `^y`.
+/// let found = find_key_file(path, "X.Y", &mut SpecifyParser::new(), &options).unwrap();
+/// let lines: Vec<_> = found[path].iter().map(|found| found.line).collect();
+/// assert_eq!(lines, [2]);
+/// ```
+pub fn find_key_file(
+    filename: &str,
+    key: &str,
+    parser: &mut SpecifyParser,
+    options: &SearchOptions,
+) -> Result<FindLines, SearchError> {
+    let compressed = options.search_compressed && is_gzip(filename);
+    let mapped = if options.mmap && !compressed {
+        mmap::find_key_mapped(filename, key, options)?
+    } else {
+        None
+    };
+    let mut lines = match mapped {
+        Some(lines) => lines,
+        None if options.encoding.is_some() => {
+            let code = read_text(filename, compressed, options.encoding)?;
+            find_key_reader(filename, &mut code.as_bytes(), key, options)?
+        }
+        None => {
+            let file = File::open(filename).map_err(|err| SearchError::io(filename, err))?;
+            let mut reader: Box<dyn BufRead> = if compressed {
+                Box::new(BufReader::new(GzDecoder::new(file)))
+            } else {
+                Box::new(BufReader::new(file))
+            };
+            match find_key_reader(filename, &mut reader, key, options) {
+                // read the file again with the invalid bytes replaced.
+                Err(SearchError::Io { source, .. })
+                    if source.kind() == io::ErrorKind::InvalidData =>
+                {
+                    let code = read_text(filename, compressed, None)?;
+                    find_key_reader(filename, &mut code.as_bytes(), key, options)?
+                }
+                found => found?,
+            }
+        }
+    };
+    if options.needs_ast() && !is_gzip(filename) {
+        if let Ok((code, ast)) = parser.get_file_ast(filename) {
+            set_scopes(&code, &ast, &mut lines, parser.get_lang(), options);
+        }
+    }
+    Ok(into_found(filename, lines))
+}
+
+/// Read the file, decompressed if `compressed`, and decode it, see `parser_lang::decode`.
+fn read_text(
+    filename: &str,
+    compressed: bool,
+    encoding: Option<&'static Encoding>,
+) -> Result<String, SearchError> {
+    let mut bytes = Vec::new();
+    let file = File::open(filename).map_err(|err| SearchError::io(filename, err))?;
+    let read = if compressed {
+        GzDecoder::new(file).read_to_end(&mut bytes)
+    } else {
+        BufReader::new(file).read_to_end(&mut bytes)
+    };
+    read.map_err(|err| SearchError::io(filename, err))?;
+    Ok(decode(bytes, encoding))
+}
+
+/** Search the key in the content, the same as `find_key_file` but without reading a file.
+
+    *PARAM:* {name} The name of the content, the key of the result and the path of the errors.
+
+    ```rust
+    use search_code::{parser_lang::SpecifyParser, search::{find_key_content, SearchOptions}};
+
+    let options = SearchOptions::default();
+    let found =
+        find_key_content("HEAD:a.txt", "a\nb key\n", "key", &mut SpecifyParser::new(), &options)
+            .unwrap();
+    assert_eq!(found["HEAD:a.txt"][0].line, 2);
+    assert_eq!(found["HEAD:a.txt"][0].byte_offset, Some(4));
+    ```
+
+    The next symbols after each line, from the ast of the language:
+
+    ```rust
+    use search_code::{parser_lang::SpecifyParser, search::{find_key_content, SearchOptions}, Options::Lang};
+
+    let options = SearchOptions {
+        after_context_symbol: 2,
+        ..Default::default()
+    };
+    let code = "// TODO\nfn a() {}\nstruct B;\nfn c() {}\n";
+    let mut parser = SpecifyParser::from_lang(&Lang::Rust);
+    let found = find_key_content("a.rs", code, "TODO", &mut parser, &options).unwrap();
+    assert_eq!(found["a.rs"][0].next_symbols, Some(vec!["a".to_string(), "B".to_string()]));
+    ```
+*/
+pub fn find_key_content(
+    name: &str,
+    content: &str,
+    key: &str,
+    parser: &mut SpecifyParser,
+    options: &SearchOptions,
+) -> Result<FindLines, SearchError> {
+    let mut lines = find_key_reader(name, &mut content.as_bytes(), key, options)?;
+    if options.needs_ast() {
+        if let Ok(ast) = parser.get_ast(content) {
+            set_scopes(content, &ast, &mut lines, parser.get_lang(), options);
+        }
+    }
+    Ok(into_found(name, lines))
+}
+
+/// name -> the found lines, or empty if no line found.
+fn into_found(name: &str, lines: Vec<FoundLine>) -> FindLines {
+    let mut found = BTreeMap::new();
+    if !lines.is_empty() {
+        found.insert(name.to_owned(), lines);
+    }
+    found
+}
+
+/// Read the lines and find the ones containing the key, see `find_key_file`.
+fn find_key_reader(
+    filename: &str,
+    reader: &mut dyn BufRead,
+    key: &str,
+    options: &SearchOptions,
+) -> Result<Vec<FoundLine>, SearchError> {
+    let invalid_regex = |source| SearchError::InvalidRegex {
+        path: filename.to_string(),
+        source,
+    };
+    let compile = |pattern: &str| options.context_matcher(pattern).map_err(invalid_regex);
+    let matcher = options.key_matcher(key).map_err(invalid_regex)?;
+    let preceded_by = options.preceded_by.as_deref().map(compile).transpose()?;
+    let followed_by = options.followed_by.as_deref().map(compile).transpose()?;
+
+    let mut lines = Vec::new();
+    let mut line_number = 0;
+    // the byte offset of the current line start in the file.
+    let mut line_start = 0;
+    // the previous line matches `preceded_by`.
+    let mut after_preceding = false;
+    // the found line waiting for the next line to match `followed_by`.
+    let mut pending: Option<FoundLine> = None;
+    let mut buf = String::new();
+    loop {
+        if options
+            .max_count
+            .is_some_and(|max_count| lines.len() >= max_count)
+        {
+            break;
+        }
+        buf.clear();
+        let read = reader
+            .read_line(&mut buf)
+            .map_err(|err| SearchError::io(filename, err))?;
+        if read == 0 {
+            break;
+        }
+        line_number += 1;
+        let line = buf
+            .strip_suffix('\n')
+            .map(|v| v.strip_suffix('\r').unwrap_or(v))
+            .unwrap_or(&buf);
+        if let Some(found_line) = pending.take() {
+            if followed_by.as_ref().is_some_and(|v| v.is_match(line)) {
+                lines.push(found_line);
+            }
+        }
+        let is_preceded = preceded_by.is_none() || after_preceding;
+        after_preceding = preceded_by.as_ref().is_some_and(|v| v.is_match(line));
+        if let Some(line_range) = options.line_range {
+            if line_number > line_range.end {
+                break;
+            }
+            if !line_range.contains(line_number) {
+                line_start += read;
+                continue;
+            }
+        }
+        if let Some(matched) = matcher.find(line).filter(|_| is_preceded) {
+            let found_line = FoundLine {
+                line: line_number,
+                col: matched.start + 1,
+                byte_offset: Some(line_start + matched.start),
+                byte_end: Some(line_start + matched.end),
+                text: line.to_string(),
+                score: None,
+                symbol: None,
+                scope: None,
+                body_lines: None,
+                key_path: None,
+                kind: None,
+                signature: None,
+                next_symbols: None,
+            };
+            if followed_by.is_some() {
+                pending = Some(found_line);
+            } else {
+                lines.push(found_line);
+            }
+        }
+        line_start += read;
+    }
+    Ok(lines)
+}
+
+/// Set the function or the class enclosing each found line with `options.context_symbol`,
+/// the lines of the enclosing function with `options.context_function_body`,
+/// and the next symbols with `options.after_context_symbol`.
+///
+/// *NOTE:* do nothing if the language is not supported.
+fn set_scopes(
+    code: &str,
+    ast: &Tree,
+    lines: &mut [FoundLine],
+    lang: &Lang,
+    options: &SearchOptions,
+) {
+    if options.after_context_symbol > 0 {
+        set_next_symbols(code, ast, lines, lang, options);
+    }
+    let scope_kinds = get_scope_kinds(lang);
+    if scope_kinds.is_empty() {
+        return;
+    }
+    if options.context_symbol {
+        for found in lines.iter_mut() {
+            found.scope =
+                enclosing_node(ast, found, &scope_kinds).and_then(|scope| scope_name(scope, code));
+        }
+    }
+    if options.context_function_body {
+        set_body_lines(ast, lines);
+    }
+}
+
+/// Set the names of the next `options.after_context_symbol` symbols after each found line,
+/// the symbols listed like `options.list_symbols` and only of `options.kinds` if any.
+///
+/// *NOTE:* do nothing if the language is not supported.
+fn set_next_symbols(
+    code: &str,
+    ast: &Tree,
+    lines: &mut [FoundLine],
+    lang: &Lang,
+    options: &SearchOptions,
+) {
+    let Ok(tree_sitter_lang) = lang.into_treesitter_language() else {
+        return;
+    };
+    let list_options = SearchOptions {
+        list_symbols: true,
+        kinds: options.kinds.clone(),
+        ..Default::default()
+    };
+    let Ok(symbols) = get_symbols_ast("", code, ast, "", lang, &tree_sitter_lang, &list_options)
+    else {
+        return;
+    };
+    let symbols: Vec<&FoundLine> = symbols.values().flatten().collect();
+    for found in lines.iter_mut() {
+        found.next_symbols = Some(
+            symbols
+                .iter()
+                .filter(|symbol| symbol.line > found.line)
+                .take(options.after_context_symbol)
+                .filter_map(|symbol| symbol.symbol.clone())
+                .collect(),
+        );
+    }
+}
+
+/// Set the lines of the function enclosing each found line, see `FoundLine::body_lines`.
+fn set_body_lines(ast: &Tree, lines: &mut [FoundLine]) {
+    for found in lines {
+        found.body_lines = enclosing_node(ast, found, &FUNCTION_SCOPE_KINDS)
+            .map(|body| (body.start_position().row + 1, body.end_position().row + 1));
+    }
+}
+
+/// Get the innermost node of the kinds enclosing the found line, walking up the ast from the match.
+fn enclosing_node<'tree>(
+    ast: &'tree Tree,
+    found: &FoundLine,
+    kinds: &[&str],
+) -> Option<Node<'tree>> {
+    let point = Point::new(found.line - 1, found.col - 1);
+    let mut node = ast.root_node().descendant_for_point_range(point, point);
+    while let Some(current) = node {
+        if kinds.contains(&current.kind()) {
+            return Some(current);
+        }
+        node = current.parent();
+    }
+    None
+}
+
+/** Get the one-line signature of the function named by the node, like `fn parse(input: &str)`.
+
+*NOTE:* the signature is the source of the function before its body, with the whitespace
+collapsed, like the parameters in the lines. The trailing `:`, `=` or `{` is dropped.
+
+*Return:* `None` if the node is not in the head of a function.
+*/
+fn function_signature(node: Node, code: &str) -> Option<String> {
+    // the name is nested in the declarator of the c++ function.
+    let function = std::iter::successors(node.parent(), |parent| parent.parent())
+        .take(3)
+        .find(|parent| FUNCTION_SCOPE_KINDS.contains(&parent.kind()))?;
+    let mut cursor = function.walk();
+    let body = function.child_by_field_name("body").or_else(|| {
+        // the kotlin function has no body field.
+        let body = function
+            .children(&mut cursor)
+            .find(|child| child.kind().ends_with("body"));
+        body
+    });
+    let end = body.map_or(function.end_byte(), |body| body.start_byte());
+    if node.start_byte() >= end {
+        return None;
+    }
+    let head = code
+        .get(function.start_byte()..end)?
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace("( ", "(")
+        .replace(", )", ")")
+        .replace(",)", ")")
+        .replace(" )", ")");
+    Some(head.trim_end_matches([' ', ':', '=', '{', ';']).to_string())
+}
+
+/// Get the name of the scope node, like `function parse_header` for a `function_item`.
+///
+/// *NOTE:* the name is the `name` field, or the `type` field like the rust impl,
+/// or the innermost `declarator` field like the c++ function.
+fn scope_name(node: Node, code: &str) -> Option<String> {
+    let mut name = node
+        .child_by_field_name("name")
+        .or_else(|| node.child_by_field_name("type"))
+        // the heading of the markdown section.
+        .or_else(|| node.child(0)?.child_by_field_name("heading_content"));
+    if name.is_none() {
+        let mut declarator = node.child_by_field_name("declarator");
+        while let Some(current) = declarator {
+            name = Some(current);
+            declarator = current.child_by_field_name("declarator");
+        }
+    }
+    let name = name?.utf8_text(code.as_bytes()).ok()?.trim();
+    let kind = node.kind().split('_').next().unwrap_or_default();
+    Some(format!("{kind} {name}"))
+}
+
+/// Get the symbols of the content if the parser supports the language, or just search the key.
+fn get_symbols_or_key_content(
+    name: &str,
+    content: &str,
+    key: &str,
+    parser: &mut SpecifyParser,
+    options: &SearchOptions,
+) -> Result<FindLines, SearchError> {
+    if *parser.get_lang() == Lang::Any {
+        find_key_content(name, content, key, parser, options)
+    } else {
+        get_symbols_content(name, content, key, parser, options)
+    }
+}
+
+/** Get the dotted path to the node from the root of the JSON ast, like `server.ports[0]`.
+
+    *NOTE:* the keys of the enclosing objects are joined with `.`,
+    the indexes of the enclosing arrays are in `[]`.
+
+    ```rust
+    use search_code::{parser_lang::SpecifyParser, search::json_key_path, Options::Lang};
+
+    let code = r#"{"server": {"ports": [80, {"name": "https"}]}}"#;
+    let ast = SpecifyParser::from_lang(&Lang::Json).get_ast(code).unwrap();
+    let name = code.find("name").unwrap();
+    let node = ast.root_node().descendant_for_byte_range(name, name).unwrap();
+    assert_eq!(json_key_path(node, code), "server.ports[1].name");
+    ```
+*/
+pub fn json_key_path(node: Node, code: &str) -> String {
+    let mut segments = Vec::new();
+    let mut child = node;
+    while let Some(parent) = child.parent() {
+        match parent.kind() {
+            "pair" => {
+                if let Some(key) = parent
+                    .child_by_field_name("key")
+                    .and_then(|key| key.utf8_text(code.as_bytes()).ok())
+                {
+                    segments.push(key.trim_matches('"').to_string());
+                }
+            }
+            "array" => {
+                let mut cursor = parent.walk();
+                let index = parent
+                    .named_children(&mut cursor)
+                    .filter(|element| !element.is_extra())
+                    .position(|element| element.id() == child.id())
+                    .unwrap_or_default();
+                segments.push(format!("[{index}]"));
+            }
+            _ => {}
+        }
+        child = parent;
+    }
+    segments.reverse();
+    segments.iter().fold(String::new(), |mut path, segment| {
+        if !path.is_empty() && !segment.starts_with('[') {
+            path.push('.');
+        }
+        path.push_str(segment);
+        path
+    })
+}
+
+/** Get the type and the labels of the HCL block labeled by the node, like `resource "aws_instance" "web"`.
+
+    *NOTE:* the headers of the enclosing blocks are joined with ` > `, from the outermost one.
+
+    *Return:* `None` if the node isn't in a block.
+
+    ```rust
+    use search_code::{parser_lang::SpecifyParser, search::hcl_block_path, Options::Lang};
+
+    let code = "resource \"aws_instance\" \"web\" {\n  ami = \"ami-1\"\n}\n";
+    let ast = SpecifyParser::from_lang(&Lang::Hcl).get_ast(code).unwrap();
+    let web = code.find("web").unwrap();
+    let node = ast.root_node().descendant_for_byte_range(web, web).unwrap();
+    assert_eq!(hcl_block_path(node, code).unwrap(), r#"resource "aws_instance" "web""#);
+    ```
+*/
+pub fn hcl_block_path(node: Node, code: &str) -> Option<String> {
+    let mut headers = Vec::new();
+    let mut current = Some(node);
+    while let Some(block) = current {
+        if block.kind() == "block" {
+            let mut cursor = block.walk();
+            let header: Vec<_> = block
+                .named_children(&mut cursor)
+                .take_while(|child| matches!(child.kind(), "identifier" | "string_lit"))
+                .filter_map(|child| child.utf8_text(code.as_bytes()).ok())
+                .collect();
+            headers.push(header.join(" "));
+        }
+        current = block.parent();
+    }
+    headers.reverse();
+    (!headers.is_empty()).then(|| headers.join(" > "))
+}
+
+/// Get the symbols if the parser supports the language, or just search the key.
+pub fn get_symbols_or_key(
+    filename: &str,
+    key: &str,
+    parser: &mut SpecifyParser,
+    options: &SearchOptions,
+) -> Result<FindLines, SearchError> {
+    if *parser.get_lang() == Lang::Any {
+        find_key_file(filename, key, parser, options)
+    } else {
+        get_symbols(filename, key, parser, options)
+    }
+}
+
+/** Get the symbols from the code.
+
+    *NOTE:* This function calls only if lang is not `Any`,
+    or return `SearchError::UnsupportedLanguage`.
+
+    *NOTE:* The symbols in `options.exclude_symbol` will be dropped from the result.
+
+    *NOTE:* `options.query` replaces the built-in queries of the language if set,
+    the `:?` in it is replaced with the key too.
+
+    *NOTE:* In the fuzzy symbol match, all the symbols are captured, then the ones
+    fuzzy matching the key are kept with their scores.
+    With `options.invert_symbol`, all the symbols are captured too, then the ones
+    not matching the pattern are kept.
+
+    *Return:* The vector of the symbols.
+    - The key is the filename.
+    - The value is the found symbols with their positions, and the lines contain them.
+*/
+pub fn get_symbols(
+    filename: &str,
+    key: &str,
+    parser: &mut SpecifyParser,
+    options: &SearchOptions,
+) -> Result<FindLines, SearchError> {
+    let lang = parser.get_lang().clone();
+    let tree_sitter_lang = symbols_language(filename, &lang)?;
+    let (code, ast) = parser.get_file_ast(filename)?;
+    get_symbols_ast(
+        filename,
+        &code,
+        &ast,
+        key,
+        &lang,
+        &tree_sitter_lang,
+        options,
+    )
+}
+
+/** Get the symbols from the content, the same as `get_symbols` but without reading a file.
+
+    *PARAM:* {name} The name of the content, the key of the result and the path of the errors.
+
+    ```rust
+    use search_code::{
+        parser_lang::SpecifyParser,
+        search::{get_symbols_content, SearchOptions},
+        Options::Lang,
+    };
+
+    let mut parser = SpecifyParser::from_lang(&Lang::Rust);
+    let options = SearchOptions::default();
+    let found =
+        get_symbols_content("HEAD:a.rs", "\nfn target() {}\n", "target", &mut parser, &options)
+            .unwrap();
+    let found = &found["HEAD:a.rs"][0];
+    assert_eq!((found.line, found.col), (2, 4));
+    assert_eq!((found.byte_offset, found.byte_end), (Some(4), Some(10)));
+    ```
+
+    Any key is safe to put in the query, the invalid regex is matched literally:
+
+    ```rust
+    use search_code::{
+        parser_lang::SpecifyParser,
+        search::{get_symbols_content, SearchOptions},
+        Options::{Lang, SymbolMatch},
+    };
+
+    let code = "fn get_a() {}\nfn get_b() {}\nfn v1() {}\n";
+    let mut parser = SpecifyParser::from_lang(&Lang::Rust);
+    let names = |key: &str, options: &SearchOptions, parser: &mut SpecifyParser| {
+        let found = get_symbols_content("a.rs", code, key, parser, options).unwrap();
+        found.values().flatten().filter_map(|found| found.symbol.clone()).collect::<Vec<_>>()
+    };
+
+    let options = SearchOptions::default();
+    assert_eq!(names("get_.", &options, &mut parser), ["get_a", "get_b"]);
+    for key in ["*", "get_a(", "foo:?", "say\"hi", "a\\"] {
+        assert!(names(key, &options, &mut parser).is_empty(), "{key}");
+    }
+    let exact = SearchOptions {
+        symbol_match: SymbolMatch::Exact,
+        ..Default::default()
+    };
+    assert!(names("get_.", &exact, &mut parser).is_empty());
+    let inverted = SearchOptions {
+        invert_symbol: true,
+        ..Default::default()
+    };
+    assert_eq!(names(r"^v\d$", &inverted, &mut parser), ["get_a", "get_b"]);
+    ```
+*/
+pub fn get_symbols_content(
+    name: &str,
+    content: &str,
+    key: &str,
+    parser: &mut SpecifyParser,
+    options: &SearchOptions,
+) -> Result<FindLines, SearchError> {
+    let lang = parser.get_lang().clone();
+    let tree_sitter_lang = symbols_language(name, &lang)?;
+    let ast = parser.get_ast(content).map_err(|_| SearchError::Parse {
+        path: name.to_string(),
+    })?;
+    if options.warn_parse_errors {
+        warn_parse_errors(name, &ast, options, &ProgressBar::hidden());
+    }
+    get_symbols_ast(name, content, &ast, key, &lang, &tree_sitter_lang, options)
+}
+
+/// Tell the file has syntax errors, its symbols may be missed or wrong, see `parse_errors`.
+///
+/// *NOTE:* nothing is told with `options.quiet`. The warning is printed above the progress bar.
+fn warn_parse_errors(
+    filename: &str,
+    ast: &Tree,
+    options: &SearchOptions,
+    progress_bar: &ProgressBar,
+) {
+    let errors = parse_errors(ast);
+    let Some(first) = errors.first().filter(|_| !options.quiet) else {
+        return;
+    };
+    progress_bar.suspend(|| {
+        eprintln!(
+            "{}",
+            format!(
+                "{filename}: {} parse errors, the first at {}:{}, the symbols may be incomplete",
+                errors.len(),
+                first.row + 1,
+                first.column + 1
+            )
+            .yellow()
+        )
+    });
+}
+
+/// Tell the searched file has syntax errors with `options.warn_parse_errors` in the symbol search,
+/// see `warn_parse_errors`.
+///
+/// *NOTE:* the ast is the cached one of the symbol search with `options.cache_asts`,
+/// or the file is parsed again.
+fn warn_file_parse_errors(
+    path: &str,
+    parser: &mut SpecifyParser,
+    options: &SearchOptions,
+    progress_bar: &ProgressBar,
+) {
+    if !options.is_symbol || !options.warn_parse_errors || *parser.get_lang() == Lang::Any {
+        return;
+    }
+    if let Ok((_, ast)) = parser.get_file_ast(path) {
+        warn_parse_errors(path, &ast, options, progress_bar);
+    }
+}
+
+/// The tree-sitter language to search the symbols, or `SearchError::UnsupportedLanguage`.
+fn symbols_language(filename: &str, lang: &Lang) -> Result<Language, SearchError> {
+    lang.into_treesitter_language()
+        .map_err(|lang| SearchError::UnsupportedLanguage {
+            path: filename.to_string(),
+            lang,
+        })
+}
+
+/// Get the symbols from the parsed code, see `get_symbols`.
+fn get_symbols_ast(
+    filename: &str,
+    code: &str,
+    ast: &Tree,
+    key: &str,
+    lang: &Lang,
+    tree_sitter_lang: &Language,
+    options: &SearchOptions,
+) -> Result<FindLines, SearchError> {
+    let mut query_cursor = QueryCursor::new();
+    let mut symbols_map = BTreeMap::new();
+    let mut pattern = options.symbol_match.pattern(key);
+    // no symbol name is empty, so all the symbols are listed for the inverted empty key.
+    let list_all = options.list_symbols || (options.invert_symbol && key.is_empty());
+    let inverted = if list_all {
+        pattern.clear();
+        None
+    // capture all the symbols, and drop the matching ones below.
+    } else if options.invert_symbol && options.symbol_match != SymbolMatch::Fuzzy {
+        // the pattern is escaped for the query, so the regex is built from the key again.
+        let matcher = Regex::new(&options.symbol_match.regex(key)).map_err(|source| {
+            SearchError::InvalidRegex {
+                path: filename.to_string(),
+                source: source.into(),
+            }
+        })?;
+        pattern.clear();
+        Some(matcher)
+    } else {
+        None
+    };
+    let fuzzy_matcher = SkimMatcherV2::default();
+    let source_lines: Vec<&str> = code.lines().collect();
+    // the node captured by more than one query is found once.
+    let mut captured = HashSet::new();
+
+    let queries = match &options.query {
+        Some(query) => vec![query.as_str()],
+        None => get_kind_query(lang, &options.kinds),
+    };
+    for search_query in queries {
+        let query = Query::new(
+            tree_sitter_lang,
+            search_query.replace(":?", &pattern).as_str(),
+        )
+        .map_err(|source| SearchError::InvalidQuery {
+            path: filename.to_string(),
+            source,
+        })?;
+        let mut captures = query_cursor.captures(&query, ast.root_node(), code.as_bytes());
+        while let Some((cs, cs_index)) = captures.next() {
+            let capture = cs.captures[*cs_index];
+            let node = capture.node;
+            if options.list_symbols
+                && LIST_SKIPPED_CAPTURES.contains(&query.capture_names()[capture.index as usize])
+            {
+                continue;
+            }
+            if !captured.insert(node.start_byte()) {
+                continue;
+            }
+            let text = node
+                .utf8_text(code.as_bytes())
+                .map_err(|_| SearchError::Parse {
+                    path: filename.to_string(),
+                })?;
+            if options.exclude_symbol.iter().any(|v| v == text)
+                || inverted
+                    .as_ref()
+                    .is_some_and(|matcher| matcher.is_match(text))
+            {
+                continue;
+            }
+            let score = if options.symbol_match == SymbolMatch::Fuzzy && !list_all {
+                let Some(score) = fuzzy_matcher.fuzzy_match(text, key) else {
+                    continue;
+                };
+                Some(score)
+            } else {
+                None
+            };
+            symbols_map
+                .entry(filename.to_owned())
+                .or_insert_with(Vec::new)
+                .push(FoundLine {
+                    line: node.start_position().row + 1,
+                    col: node.start_position().column + 1,
+                    byte_offset: Some(node.start_byte()),
+                    byte_end: Some(node.end_byte()),
+                    text: source_lines
+                        .get(node.start_position().row)
+                        .map_or(text, |line| line)
+                        .to_string(),
+                    score,
+                    symbol: Some(text.to_string()),
+                    scope: None,
+                    body_lines: None,
+                    key_path: match lang {
+                        Lang::Json => Some(json_key_path(node, code)),
+                        Lang::Hcl => hcl_block_path(node, code),
+                        _ => None,
+                    },
+                    kind: Some(query.capture_names()[capture.index as usize].to_string()),
+                    signature: options
+                        .signatures
+                        .then(|| function_signature(node, code))
+                        .flatten(),
+                    next_symbols: None,
+                });
+        }
+    }
+    // the symbols of the queries are in the source order together.
+    for find in symbols_map.values_mut() {
+        find.sort_by_key(|found: &FoundLine| (found.line, found.col));
+        if options.context_function_body {
+            set_body_lines(ast, find);
+        }
+        if options.after_context_symbol > 0 {
+            set_next_symbols(code, ast, find, lang, options);
+        }
+    }
+
+    Ok(symbols_map)
+}
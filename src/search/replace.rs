@@ -0,0 +1,114 @@
+/*!
+    Replace the key in the found lines, and write the files in place.
+*/
+
+use std::{
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+};
+
+use super::{error::SearchError, matcher::KeyMatcher, FindLines};
+
+/** Replace the matches in the given lines of the code, the other lines are kept.
+
+*PARAM:* {expand} Expand the `$1` and the `${name}` in the replacement to the captured groups,
+and `$$` to `$`, for the regex key. Or the replacement is literal, see `KeyMatcher::replacements`.
+
+*PARAM:* {lines} The 1-based line numbers to replace in.
+
+*Return:* (the replaced code, the count of the replaced matches).
+
+```rust
+use regex::Regex;
+use search_code::search::{matcher::KeyMatcher, replace::replace_lines};
+
+let matcher = KeyMatcher::from(Regex::new("a").unwrap());
+let (code, count) = replace_lines("a a\nb a\r\na\n", &matcher, "$c", false, &[1, 3]);
+assert_eq!(code, "$c $c\nb a\r\n$c\n");
+assert_eq!(count, 3);
+
+let matcher = KeyMatcher::from(Regex::new(r"fn (\w+)\((?<args>[^)]*)\)").unwrap());
+let code = "fn main() {}\nfn add(a: i32) {}\n";
+let (code, count) = replace_lines(code, &matcher, "function $1(${args}) $$", true, &[1, 2]);
+assert_eq!(code, "function main() $ {}\nfunction add(a: i32) $ {}\n");
+assert_eq!(count, 2);
+```
+*/
+pub fn replace_lines(
+    code: &str,
+    matcher: &KeyMatcher,
+    replacement: &str,
+    expand: bool,
+    lines: &[usize],
+) -> (String, usize) {
+    let mut replaced = String::with_capacity(code.len());
+    let mut count = 0;
+    for (index, line) in code.split_inclusive('\n').enumerate() {
+        if !lines.contains(&(index + 1)) {
+            replaced.push_str(line);
+            continue;
+        }
+        let text = line
+            .strip_suffix('\n')
+            .map(|v| v.strip_suffix('\r').unwrap_or(v))
+            .unwrap_or(line);
+        count += matcher
+            .find_iter(text)
+            .filter(|range| !range.is_empty())
+            .count();
+        replaced.push_str(&matcher.replace_all(text, replacement, expand));
+        replaced.push_str(&line[text.len()..]);
+    }
+    (replaced, count)
+}
+
+/// Replace the matches in the found lines of each file, and write the files.
+///
+/// *NOTE:* the `$` in the replacement is expanded if `expand`, see `replace_lines`.
+///
+/// *NOTE:* each file is written to a temp file in the same dir first, then renamed
+/// to the file, so a crash never leaves a partially written file.
+///
+/// *Return:* The count of the replaced matches.
+pub fn write_replaced(
+    found: &FindLines,
+    matcher: &KeyMatcher,
+    replacement: &str,
+    expand: bool,
+) -> Result<usize, SearchError> {
+    let mut count = 0;
+    for (filename, find) in found {
+        let code = fs::read_to_string(filename).map_err(|err| SearchError::io(filename, err))?;
+        let lines: Vec<usize> = find.iter().map(|found| found.line).collect();
+        let (replaced, replaced_count) = replace_lines(&code, matcher, replacement, expand, &lines);
+        if replaced_count == 0 {
+            continue;
+        }
+        write_atomic(Path::new(filename), &replaced)
+            .map_err(|err| SearchError::io(filename, err))?;
+        count += replaced_count;
+    }
+    Ok(count)
+}
+
+/// Write the content to a temp file beside the path, then rename it to the path.
+///
+/// *NOTE:* the temp file gets the permissions of the replaced file.
+fn write_atomic(path: &Path, content: &str) -> Result<(), io::Error> {
+    let filename = path
+        .file_name()
+        .ok_or_else(|| io::Error::other("not a file"))?;
+    let temp = path.with_file_name(format!(".{}.search_code.tmp", filename.to_string_lossy()));
+    let written = (|| {
+        let mut file = File::create(&temp)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        fs::set_permissions(&temp, fs::metadata(path)?.permissions())?;
+        fs::rename(&temp, path)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    written
+}
@@ -0,0 +1,172 @@
+/*! The interactive browser of the search result.
+
+    The files are listed on the left and the found lines of the selected file on the right.
+    - `Up`/`Down` or `k`/`j`: select the file or the line.
+    - `Left`/`Right`, `h`/`l` or `Tab`: switch between the files and the lines.
+    - `Enter`: open the selected line in `$EDITOR`.
+    - `q` or `Esc`: quit.
+*/
+
+use std::{io, process::Command};
+
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, List, ListState},
+    DefaultTerminal, Frame,
+};
+
+use crate::search::{FindLines, FoundLine};
+
+/// The list to move in with the arrow keys.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Focus {
+    Files,
+    Lines,
+}
+
+/// The state of the browser.
+struct Browser<'a> {
+    files: Vec<(&'a String, &'a Vec<FoundLine>)>,
+    file_state: ListState,
+    line_state: ListState,
+    focus: Focus,
+}
+
+/// Browse the result in the terminal until quit.
+///
+/// *NOTE:* do nothing if the result is empty.
+pub fn browse(result: &FindLines) -> Result<(), io::Error> {
+    if result.is_empty() {
+        return Ok(());
+    }
+    let mut browser = Browser {
+        files: result.iter().collect(),
+        file_state: ListState::default().with_selected(Some(0)),
+        line_state: ListState::default().with_selected(Some(0)),
+        focus: Focus::Files,
+    };
+    let mut terminal = ratatui::init();
+    let browsed = browser.run(&mut terminal);
+    ratatui::restore();
+    browsed
+}
+
+impl<'a> Browser<'a> {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<(), io::Error> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Up | KeyCode::Char('k') => self.select(-1),
+                KeyCode::Down | KeyCode::Char('j') => self.select(1),
+                KeyCode::Left | KeyCode::Char('h') => self.focus = Focus::Files,
+                KeyCode::Right | KeyCode::Char('l') => self.focus = Focus::Lines,
+                KeyCode::Tab => {
+                    self.focus = match self.focus {
+                        Focus::Files => Focus::Lines,
+                        Focus::Lines => Focus::Files,
+                    }
+                }
+                KeyCode::Enter => {
+                    // give the terminal to the editor, then take it back.
+                    ratatui::restore();
+                    let opened = self.open_editor();
+                    *terminal = ratatui::init();
+                    opened?;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn selected_file(&self) -> (&'a String, &'a Vec<FoundLine>) {
+        self.files[self.file_state.selected().unwrap_or(0)]
+    }
+
+    /// Move the selection of the focused list by `step`, stop at the first or the last one.
+    fn select(&mut self, step: isize) {
+        let (state, len) = match self.focus {
+            Focus::Files => (&mut self.file_state, self.files.len()),
+            Focus::Lines => {
+                let len = self.selected_file().1.len();
+                (&mut self.line_state, len)
+            }
+        };
+        let selected = state.selected().unwrap_or(0).saturating_add_signed(step);
+        state.select(Some(selected.min(len.saturating_sub(1))));
+        if self.focus == Focus::Files {
+            self.line_state.select(Some(0));
+        }
+    }
+
+    /// Open the selected line in `$EDITOR`, `vi` if not set.
+    fn open_editor(&self) -> Result<(), io::Error> {
+        let (filename, find) = self.selected_file();
+        let Some(found) = find.get(self.line_state.selected().unwrap_or(0)) else {
+            return Ok(());
+        };
+        let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+        let mut words = editor.split_whitespace();
+        let mut command = Command::new(words.next().unwrap_or("vi"));
+        command.args(words);
+        // VS Code like editors take `-g file:line`, the others `+line file`.
+        if editor.ends_with("code") || editor.contains("code ") {
+            command.arg("-g").arg(format!("{filename}:{}", found.line));
+        } else {
+            command.arg(format!("+{}", found.line)).arg(filename);
+        }
+        command.status().map(|_| ())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [files_area, lines_area] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .areas(frame.area());
+        let border = |focus: Focus| {
+            if self.focus == focus {
+                Style::new().fg(Color::Cyan)
+            } else {
+                Style::new()
+            }
+        };
+
+        let files = List::new(
+            self.files
+                .iter()
+                .map(|(filename, find)| format!("{filename} ({})", find.len())),
+        )
+        .block(
+            Block::bordered()
+                .title("Files")
+                .border_style(border(Focus::Files)),
+        )
+        .highlight_style(Style::new().reversed());
+
+        let (filename, find) = self.selected_file();
+        let lines = List::new(find.iter().map(|found| {
+            Line::from(vec![
+                Span::from(format!("{}:", found.line)).green(),
+                Span::from(found.text.clone()),
+            ])
+        }))
+        .block(
+            Block::bordered()
+                .title(filename.as_str())
+                .title_bottom("q: quit, Enter: open in $EDITOR")
+                .border_style(border(Focus::Lines)),
+        )
+        .highlight_style(Style::new().reversed());
+
+        frame.render_stateful_widget(files, files_area, &mut self.file_state);
+        frame.render_stateful_widget(lines, lines_area, &mut self.line_state);
+    }
+}
@@ -0,0 +1,67 @@
+/*! Watch the searched paths and wait for the files to change.
+
+    Used by the `--watch` mode to search again after the files change.
+*/
+
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    time::Duration,
+};
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+
+/// The time to wait for more changes after one, so a burst of changes is one batch.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Watch the files and the dirs recursively.
+pub struct Watcher {
+    // the watcher stops when dropped.
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+}
+
+impl Watcher {
+    /// Start watching the paths, the dirs are watched recursively.
+    pub fn new(paths: &[&Path]) -> Result<Self, notify::Error> {
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        for path in paths {
+            watcher.watch(path, RecursiveMode::Recursive)?;
+        }
+        Ok(Watcher {
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    /// Block until some files are created, modified or removed.
+    ///
+    /// *NOTE:* the changes within `DEBOUNCE` of each other are returned together,
+    /// the errors of the watcher are dropped.
+    ///
+    /// *Return:* The changed paths, may be duplicated. Empty if the watcher stopped.
+    pub fn wait(&self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        let Ok(event) = self.events.recv() else {
+            return changed;
+        };
+        push_changed(&mut changed, event);
+        while let Ok(event) = self.events.recv_timeout(DEBOUNCE) {
+            push_changed(&mut changed, event);
+        }
+        changed
+    }
+}
+
+/// Push the paths of the event if it changes the files, not just reads them.
+fn push_changed(changed: &mut Vec<PathBuf>, event: notify::Result<Event>) {
+    if let Ok(event) = event {
+        if matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+        ) {
+            changed.extend(event.paths);
+        }
+    }
+}